@@ -1,88 +1,188 @@
 // Copyright 2022 Vladislav Melnik
 // SPDX-License-Identifier: MIT
 
-use super::{find_attr, Tags};
+use super::{
+    find_attr, field_limits, is_other, skip_fields, skipped_value, Layout, Tags, RadiationAttr,
+};
+
+pub fn derive(mut s: synstructure::Structure) -> proc_macro2::TokenStream {
+    if let Err(err) = skip_fields(&mut s) {
+        return err.into_compile_error();
+    }
 
-pub fn derive(s: synstructure::Structure) -> proc_macro2::TokenStream {
     let (tags, tag_ty) = match Tags::new(&s) {
         Ok(v) => v,
         Err(err) => return err.into_compile_error(),
     };
 
+    let limit_self = match RadiationAttr::new(&s.ast().attrs).and_then(|attr| attr.limit()) {
+        Ok(v) => v,
+        Err(err) => return err.into_compile_error(),
+    };
+
     let se = quote::quote!(radiation);
+    let ident = &s.ast().ident;
     let mut body = quote::quote!();
+    let mut fallback = None;
     for t in tags {
         let (tag_val, variant) = match t {
             Ok(v) => v,
             Err(err) => return err.to_compile_error(),
         };
 
-        let ctor = variant.construct(|_field, i| {
-            format!("_{i}")
-                .parse::<proc_macro2::TokenStream>()
-                .expect("trivial code")
+        // the `other` variant takes the tag that is already absorbed
+        let other = match is_other(variant) {
+            Ok(v) => v,
+            Err(err) => return err.into_compile_error(),
+        };
+        let first = usize::from(other);
+        let bindings = &variant.bindings()[first..];
+
+        // the skipped fields are not bound, the others are numbered in order
+        let mut bound = 0;
+        let ctor = variant.construct(|field, _| match skipped_value(field) {
+            Some(value) => value,
+            None if other && bound == 0 => {
+                bound += 1;
+                quote::quote!(tag)
+            }
+            None => {
+                bound += 1;
+                format!("_{}", bound - 1)
+                    .parse::<proc_macro2::TokenStream>()
+                    .expect("trivial code")
+            }
         });
-        let parser = match variant.bindings().len() {
-            0 => quote::quote! { #se::nom::combinator::success(#ctor) },
-            len => {
-                let mut p = quote::quote!();
-                let mut limit_next = quote::quote!(L);
-                for binding in variant.bindings() {
-                    let ast = &binding.ast();
-                    let limit =
-                        extract_attr!(&ast.attrs, "limit").unwrap_or_else(|| limit_next.clone());
-                    limit_next = quote::quote!(<#limit as #se::Limit>::Next);
-                    let as_str = find_attr(&ast.attrs, "as_str").is_some();
-                    let custom_absorb = extract_attr!(&ast.attrs, "custom_absorb");
+        let layout = match Layout::new(bindings) {
+            Ok(v) => v,
+            Err(err) => return err.into_compile_error(),
+        };
+        let limits = match field_limits(bindings, &limit_self) {
+            Ok(v) => v,
+            Err(err) => return err.into_compile_error(),
+        };
+        // the version of the layout, the `version` field is absorbed below
+        let mut p = if layout.versioned() && layout.version.is_none() {
+            quote::quote!(let version = <#limit_self as #se::Limit>::VERSION;)
+        } else {
+            quote::quote!()
+        };
+        for (i, (binding, limit)) in bindings.iter().zip(limits).enumerate() {
+            let ast = &binding.ast();
+            let as_str = find_attr(&ast.attrs, "as_str").is_some();
+            let custom_absorb = extract_attr!(&ast.attrs, "custom_absorb");
+
+            let field = if as_str {
+                quote::quote!(#se::__private::absorb_parsed::<_, #limit>)
+            } else if let Some(absorb) = custom_absorb {
+                quote::quote!(#absorb)
+            } else {
+                quote::quote!(#se::Absorb::absorb::<#limit>)
+            };
+            let name = match &ast.ident {
+                Some(ident) => ident.to_string(),
+                None => (first + i).to_string(),
+            };
+            let field = quote::quote!(#se::with_path(#se::PathSegment::Field(#name), #field));
+            let var = format!("_{}", first + i)
+                .parse::<proc_macro2::TokenStream>()
+                .expect("trivial code");
 
-                    if as_str {
-                        p.extend(quote::quote!(
-                            #se::nom::combinator::map_res(<&str>::absorb::<#limit>, str::parse),
-                        ));
-                    } else if let Some(absorb) = custom_absorb {
-                        p.extend(quote::quote!(#absorb,));
+            p.extend(match layout.condition(i) {
+                None => quote::quote!(let (input, #var) = #field(input)?;),
+                Some(condition) => quote::quote! {
+                    let (input, #var) = if #condition {
+                        let (input, value) = #field(input)?;
+                        (input, Some(value))
                     } else {
-                        p.extend(quote::quote!(#se::Absorb::absorb::<#limit>,));
+                        (input, None)
                     };
-                }
-
-                let mut pat = quote::quote!();
-                for i in 0..len {
-                    let var = format!("_{i},")
-                        .parse::<proc_macro2::TokenStream>()
-                        .expect("trivial code");
-                    pat.extend(var);
-                }
+                },
+            });
+            if layout.version == Some(i) && layout.versioned() {
+                let ty = &ast.ty;
+                p.extend(quote::quote!(let version = <u64 as From<#ty>>::from(#var);));
+            }
+        }
 
-                quote::quote! {
-                    #se::nom::combinator::map(#se::nom::sequence::tuple((#p)), |(#pat)| #ctor)
-                }
+        let parser = quote::quote! {
+            |input: &'pa [u8]| -> #se::nom::IResult<&'pa [u8], Self, #se::ParseError<&'pa [u8]>> {
+                #p
+                Ok((input, #ctor))
             }
         };
 
-        body.extend(quote::quote! {
-            if tag == #tag_val {
-                #parser(input)
-            } else
-        })
+        let parser = if let syn::Data::Enum(_) = &s.ast().data {
+            let name = variant.ast().ident.to_string();
+            quote::quote!(#se::with_path(#se::PathSegment::Variant(#name), #parser))
+        } else {
+            parser
+        };
+
+        if other {
+            fallback = Some(parser);
+        } else {
+            body.extend(quote::quote! {
+                if tag == #tag_val {
+                    #parser(input)
+                } else
+            })
+        }
     }
+    let (original, fallback) = match fallback {
+        Some(parser) => (quote::quote!(), quote::quote!(#parser(input))),
+        None => (
+            quote::quote!(let original_input = <&[u8]>::clone(&input);),
+            quote::quote! {
+            let kind = #se::ParseErrorKind::unknown_tag(tag, stringify!(#ident));
+                Err(kind.error(original_input))
+            },
+        ),
+    };
+
+    // the borrowed fields, e.g. `&'a str` or `Cow<'a, [u8]>`, borrow from the input,
+    // so the input outlives them, the lifetime named `'pa` is the input itself
+    let mut pa_declared = false;
+    let lifetimes = s
+        .ast()
+        .generics
+        .lifetimes()
+        .map(|param| param.lifetime.clone())
+        .collect::<Vec<_>>();
+    for lifetime in lifetimes {
+        if lifetime.ident == "pa" {
+            pa_declared = true;
+        } else {
+            s.add_where_predicate(syn::parse_quote!('pa: #lifetime));
+        }
+    }
+    let pa = if pa_declared {
+        quote::quote!()
+    } else {
+        quote::quote!(<'pa>)
+    };
 
-    let ident = &s.ast().ident;
     let gen_impl = quote! {
-        gen impl<'pa> #se::Absorb<'pa> for @Self {
+        gen impl #pa #se::Absorb<'pa> for @Self {
             fn absorb<L>(
                 input: &'pa [u8],
             ) -> #se::nom::IResult<&'pa [u8], Self, #se::ParseError<&'pa [u8]>>
             where
                 L: #se::Limit,
             {
-                let original_input = <&[u8]>::clone(&input);
-                let (input, tag) = #tag_ty::absorb::<()>(input)?;
-                #body
-                {
-                    let kind = #se::ParseErrorKind::unknown_tag(tag, stringify!(#ident));
-                    Err(kind.error(original_input))
-                }
+                let absorb = |input: &'pa [u8]| -> #se::nom::IResult<
+                    &'pa [u8],
+                    Self,
+                    #se::ParseError<&'pa [u8]>,
+                > {
+                    #original
+                    let (input, tag) = <#tag_ty>::absorb::<#se::Inherit<(), #limit_self>>(input)?;
+                    #body
+                    {
+                        #fallback
+                    }
+                };
+                #se::with_path(#se::PathSegment::Type(stringify!(#ident)), absorb)(input)
             }
         }
     };
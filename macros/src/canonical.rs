@@ -0,0 +1,54 @@
+// Copyright 2022 Vladislav Melnik
+// SPDX-License-Identifier: MIT
+
+pub fn derive(mut s: synstructure::Structure) -> proc_macro2::TokenStream {
+    let se = quote::quote!(radiation);
+
+    // the value is absorbed from the local bytes, so the lifetimes are
+    // inferred instead of taken from `Self`
+    let ast = s.ast();
+    let ident = &ast.ident;
+    let params = ast
+        .generics
+        .params
+        .iter()
+        .map(|param| match param {
+            syn::GenericParam::Lifetime(_) => quote::quote!('_),
+            syn::GenericParam::Type(param) => {
+                let ident = &param.ident;
+                quote::quote!(#ident)
+            }
+            syn::GenericParam::Const(param) => {
+                let ident = &param.ident;
+                quote::quote!(#ident)
+            }
+        })
+        .collect::<Vec<_>>();
+    let ty = quote::quote!(#ident<#(#params),*>);
+
+    let types = ast
+        .generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect::<Vec<_>>();
+    s.add_bounds(synstructure::AddBounds::None);
+    // no bound on `Self`, it would pin the lifetimes of the absorbed value
+    for ident in types {
+        s.add_where_predicate(syn::parse_quote!(
+            #ident: #se::Emit<Vec<u8>> + for<'pa> #se::Absorb<'pa>
+        ));
+    }
+
+    s.gen_impl(quote! {
+        gen impl #se::IsCanonical for @Self {
+            fn is_canonical(&self) -> bool {
+                #se::__private::is_canonical(self, |bytes: &[u8]| {
+                    match <#ty as #se::Absorb<'_>>::absorb::<#se::Canonical>(bytes) {
+                        Ok((&[], v)) => #se::Emit::chain_canonical(&v, vec![]).ok(),
+                        _ => None,
+                    }
+                })
+            }
+        }
+    })
+}
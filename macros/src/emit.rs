@@ -1,61 +1,291 @@
 // Copyright 2022 Vladislav Melnik
 // SPDX-License-Identifier: MIT
 
-use super::{Tags, find_attr};
+use super::{Tags, find_attr, field_limits, is_other, skip_fields, Layout, RadiationAttr};
+
+pub fn derive(mut s: synstructure::Structure) -> proc_macro2::TokenStream {
+    if let Err(err) = skip_fields(&mut s) {
+        return err.into_compile_error();
+    }
 
-pub fn derive(s: synstructure::Structure) -> proc_macro2::TokenStream {
     let (tags, tag_ty) = match Tags::new(&s) {
         Ok(v) => v,
         Err(err) => return err.into_compile_error(),
     };
 
+    let limit_self = match RadiationAttr::new(&s.ast().attrs).and_then(|attr| attr.limit()) {
+        Ok(v) => v,
+        Err(err) => return err.into_compile_error(),
+    };
+
     let se = quote::quote!(radiation);
     let mut body = quote::quote!();
+    let mut len_body = quote::quote!();
+    let mut measured_body = quote::quote!();
+    let mut size = None;
     for t in tags {
         let (tag_val, variant) = match t {
             Ok(v) => v,
             Err(err) => return err.to_compile_error(),
         };
 
-        let init = quote::quote! { #tag_ty::emit(&(#tag_val), buffer); };
-        body.extend(variant.fold(init, |acc, binding| {
-            let ast = &binding.ast();
-            let as_str = find_attr(&ast.attrs, "as_str").is_some();
-            let custom_emit = extract_attr!(&ast.attrs, "custom_emit");
+        // the `other` variant emits the captured tag instead of its own
+        let other = match is_other(variant) {
+            Ok(v) => v,
+            Err(err) => return err.into_compile_error(),
+        };
+        let (tag_val, bindings) = if other {
+            let tag = &variant.bindings()[0].binding;
+            (quote::quote!(*#tag), &variant.bindings()[1..])
+        } else {
+            (tag_val, variant.bindings())
+        };
 
-            let i = &binding.binding;
+        let limits = match field_limits(bindings, &limit_self) {
+            Ok(v) => v,
+            Err(err) => return err.into_compile_error(),
+        };
+        let layout = match Layout::new(bindings) {
+            Ok(v) => v,
+            Err(err) => return err.into_compile_error(),
+        };
+        let version = match layout.version {
+            Some(k) => {
+                let (version, ty) = (&bindings[k].binding, &bindings[k].ast().ty);
+                quote::quote!(<u64 as From<#ty>>::from(*#version))
+            }
+            None => quote::quote!(<#limit_self as #se::Limit>::VERSION),
+        };
+        let since = |k: usize| {
+            layout.fields[k]
+                .since
+                .map(|n| quote::quote!(#version >= #n))
+        };
+        // the absent trailing optional field is followed only by absent ones
+        let trailing = (0..bindings.len())
+            .filter(|k| layout.fields[*k].trailing)
+            .collect::<Vec<_>>();
+        let names = bindings
+            .iter()
+            .enumerate()
+            .map(|(k, binding)| match &binding.ast().ident {
+                Some(ident) => ident.to_string(),
+                None => (usize::from(other) + k).to_string(),
+            })
+            .collect::<Vec<_>>();
 
-            if as_str {
-                quote::quote! {
-                    #acc
-                    alloc::string::ToString::to_string(#i).emit(buffer);
+        let tag_len = quote::quote! {
+            <#tag_ty as #se::EncodedLen>::encoded_len_with::<#se::Inherit<(), #limit_self>>(&(#tag_val))
+        };
+        let fields_len = bindings.iter().zip(&limits).enumerate().fold(
+            quote::quote!(0usize),
+            |acc, (k, (binding, limit))| {
+                let ast = &binding.ast();
+                let as_str = find_attr(&ast.attrs, "as_str").is_some();
+                let custom_emit = extract_attr!(&ast.attrs, "custom_emit");
+
+                let i = &binding.binding;
+                let value = if layout.fields[k].optional() {
+                    quote::quote!(value)
+                } else {
+                    quote::quote!(#i)
+                };
+
+                let len = if as_str {
+                    quote::quote! {
+                        #se::EncodedLen::encoded_len_with::<#limit>(&alloc::string::ToString::to_string(#value))
+                    }
+                } else if let Some(custom_emit) = custom_emit {
+                    quote::quote! {
+                        {
+                            let mut counter = #se::__private::Counter::default();
+                            let _ = #custom_emit(#value, &mut counter);
+                            #se::RadiationBuffer::pos(&counter)
+                        }
+                    }
+                } else {
+                    quote::quote! { #se::EncodedLen::encoded_len_with::<#limit>(#value) }
+                };
+                if !layout.fields[k].optional() {
+                    return quote::quote! { #acc + #len };
+                }
+                let len = quote::quote! {
+                    match #i {
+                        Some(value) => #len,
+                        None => 0,
+                    }
+                };
+                match since(k) {
+                    Some(since) => quote::quote! { #acc + if #since { #len } else { 0 } },
+                    None => quote::quote! { #acc + #len },
+                }
+            },
+        );
+        let pat = variant.pat();
+        len_body.extend(quote::quote!(#pat => { #tag_len + #fields_len }));
+        let variant_size = bindings.iter().zip(&layout.fields).fold(
+            quote::quote!(<#tag_ty as #se::EncodedLen>::SIZE),
+            |acc, (binding, presence)| {
+                let ast = &binding.ast();
+                let ty = &ast.ty;
+                if presence.optional()
+                    || find_attr(&ast.attrs, "as_str").is_some()
+                    || find_attr(&ast.attrs, "custom_emit").is_some()
+                {
+                    quote::quote!(None)
+                } else {
+                    quote::quote!(#se::__private::size_sum(#acc, <#ty as #se::EncodedLen>::SIZE))
                 }
-            } else if let Some(custom_emit) = custom_emit {
+            },
+        );
+        size = Some(match size {
+            None => variant_size,
+            Some(size) => quote::quote!(#se::__private::size_same(#size, #variant_size)),
+        });
+
+        // `measured` sums the lengths of the fields, see `Emit::emit_measured`
+        let emit_fields = |measured: bool| {
+            let mut init = if measured {
                 quote::quote! {
-                    #acc
-                    #custom_emit(#i, buffer);
+                    #[allow(unused_mut)]
+                    let mut len = <#tag_ty as #se::Emit<W>>::emit_measured::<#se::Inherit<(), #limit_self>>(&(#tag_val), buffer)?;
                 }
             } else {
                 quote::quote! {
-                    #acc
-                    #i.emit(buffer);
+                    <#tag_ty as #se::Emit<W>>::emit::<#se::Inherit<(), #limit_self>>(&(#tag_val), buffer)?;
                 }
+            };
+            if trailing.len() > 1 {
+                init.extend(quote::quote!(let mut absent = None;));
             }
-        }))
+            bindings.iter().zip(&limits).enumerate().fold(init, |acc, (k, (binding, limit))| {
+                let ast = &binding.ast();
+                let as_str = find_attr(&ast.attrs, "as_str").is_some();
+                let custom_emit = extract_attr!(&ast.attrs, "custom_emit");
+
+                let i = &binding.binding;
+                let value = if layout.fields[k].optional() {
+                    quote::quote!(value)
+                } else {
+                    quote::quote!(#i)
+                };
+
+                let emit = match (as_str, custom_emit, measured) {
+                    (true, _, false) => quote::quote! {
+                        #se::Emit::<W>::emit::<#limit>(&alloc::string::ToString::to_string(#value), buffer)?;
+                    },
+                    (true, _, true) => quote::quote! {
+                        len += #se::Emit::<W>::emit_measured::<#limit>(&alloc::string::ToString::to_string(#value), buffer)?;
+                    },
+                    (false, Some(custom_emit), false) => quote::quote! {
+                        #custom_emit(#value, buffer)?;
+                    },
+                    (false, Some(custom_emit), true) => quote::quote! {
+                        len += {
+                            let mut counter = #se::__private::Counter::default();
+                            let _ = #custom_emit(#value, &mut counter);
+                            #se::RadiationBuffer::pos(&counter)
+                        };
+                        #custom_emit(#value, buffer)?;
+                    },
+                    (false, None, false) => quote::quote! {
+                        #se::Emit::<W>::emit::<#limit>(#value, buffer)?;
+                    },
+                    (false, None, true) => quote::quote! {
+                        len += #se::Emit::<W>::emit_measured::<#limit>(#value, buffer)?;
+                    },
+                };
+                if !layout.fields[k].optional() {
+                    return quote::quote! { #acc #emit };
+                }
+
+                let name = &names[k];
+                let (check, none) = if !layout.fields[k].trailing {
+                    let none = quote::quote!(return Err(#se::EmitError::MissingField(#name)));
+                    (quote::quote!(), none)
+                } else {
+                    let check = if trailing.first() != Some(&k) {
+                        quote::quote! {
+                            if let Some(name) = absent {
+                                return Err(#se::EmitError::MissingField(name));
+                            }
+                        }
+                    } else {
+                        quote::quote!()
+                    };
+                    let none = if trailing.last() != Some(&k) {
+                        quote::quote!(absent = Some(#name))
+                    } else {
+                        quote::quote!(())
+                    };
+                    (check, none)
+                };
+                let emit = quote::quote! {
+                    match #i {
+                        Some(value) => {
+                            #check
+                            #emit
+                        }
+                        None => #none,
+                    }
+                };
+                // the field of the later version is not in the layout
+                match since(k) {
+                    Some(since) => quote::quote! {
+                        #acc
+                        if #since {
+                            #emit
+                        } else if #i.is_some() {
+                            return Err(#se::EmitError::UnexpectedField(#name));
+                        }
+                    },
+                    None => quote::quote! { #acc #emit },
+                }
+            })
+        };
+        let (fields, fields_measured) = (emit_fields(false), emit_fields(true));
+        body.extend(quote::quote!(#pat => { #fields }));
+        measured_body.extend(quote::quote!(#pat => { #fields_measured len }));
     }
 
+    let size = size.unwrap_or_else(|| quote::quote!(None));
     let gen_impl = quote! {
+        gen impl #se::EncodedLen for @Self {
+            const SIZE: Option<usize> = #size;
+
+            fn encoded_len_with<L>(&self) -> usize
+            where
+                L: #se::Limit,
+            {
+                match self {
+                    #len_body
+                }
+            }
+        }
+
         gen impl<W> #se::Emit<W> for @Self
         where
-            W: for<'a> Extend<&'a u8> + #se::RadiationBuffer,
+            W: for<'__w> Extend<&'__w u8>,
         {
-            fn emit(&self, buffer: &mut W) {
+            fn emit<L>(&self, buffer: &mut W) -> Result<(), #se::EmitError>
+            where
+                L: #se::Limit,
+            {
                 match self {
                     #body
                 }
+                Ok(())
             }
-        }
 
+            fn emit_measured<L>(&self, buffer: &mut W) -> Result<usize, #se::EmitError>
+            where
+                L: #se::Limit,
+            {
+                Ok(match self {
+                    #measured_body
+                })
+            }
+        }
     };
 
     s.gen_impl(gen_impl)
@@ -11,6 +11,269 @@ fn find_attr(attrs: &[syn::Attribute], name: &str) -> Option<syn::Attribute> {
     attrs.iter().find(|a| a.path().is_ident(name)).cloned()
 }
 
+/// Items of `#[radiation(key, key = value, ...)]` attributes.
+struct RadiationAttr(Vec<(syn::Ident, Option<proc_macro2::TokenStream>)>);
+
+impl syn::parse::Parse for RadiationAttr {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        const KEYS: &[&str] = &[
+            "endian",
+            "prefix",
+            "strict",
+            "canonical",
+            "ordering",
+            "skip",
+            "default",
+            "other",
+            "version",
+            "since",
+            "trailing_optional",
+        ];
+
+        let mut items = vec![];
+        while !input.is_empty() {
+            let key = input.parse::<syn::Ident>()?;
+            if !KEYS.contains(&key.to_string().as_str()) {
+                let msg = format!("unexpected key: {key}");
+                return Err(syn::Error::new(key.span(), msg));
+            }
+            let value = if input.parse::<Option<syn::Token![=]>>()?.is_some() {
+                let mut value = proc_macro2::TokenStream::new();
+                while !input.is_empty() && !input.peek(syn::Token![,]) {
+                    value.extend(Some(input.parse::<proc_macro2::TokenTree>()?));
+                }
+                Some(value)
+            } else {
+                None
+            };
+            items.push((key, value));
+            if !input.is_empty() {
+                input.parse::<syn::Token![,]>()?;
+            }
+        }
+
+        Ok(RadiationAttr(items))
+    }
+}
+
+impl RadiationAttr {
+    fn new(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut items = vec![];
+        for attr in attrs.iter().filter(|a| a.path().is_ident("radiation")) {
+            items.extend(attr.parse_args::<RadiationAttr>()?.0);
+        }
+        Ok(RadiationAttr(items))
+    }
+
+    fn value(&self, key: &str) -> Option<&proc_macro2::TokenStream> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == key)
+            .and_then(|(_, v)| v.as_ref())
+    }
+
+    fn has(&self, key: &str) -> bool {
+        self.0.iter().any(|(k, _)| k == key)
+    }
+
+    /// The limit with `#[radiation(strict)]` and `#[radiation(canonical)]` modes,
+    /// and the ordering of atomics `#[radiation(ordering = "...")]`.
+    fn wrap_mode(&self, limit: proc_macro2::TokenStream) -> syn::Result<proc_macro2::TokenStream> {
+        let se = quote::quote!(radiation);
+        let limit = if self.has("strict") {
+            quote::quote!(#se::Strict<#limit>)
+        } else {
+            limit
+        };
+        let limit = if self.has("canonical") {
+            quote::quote!(#se::Canonical<#limit>)
+        } else {
+            limit
+        };
+        match self.value("ordering") {
+            None => Ok(limit),
+            Some(value) => match syn::parse2::<syn::LitStr>(value.clone())?.value().as_str() {
+                "acquire" => Ok(quote::quote!(#se::Acquire<#limit>)),
+                "seq_cst" => Ok(quote::quote!(#se::SeqCst<#limit>)),
+                _ => Err(syn::Error::new_spanned(
+                    value,
+                    "expected \"acquire\" or \"seq_cst\"",
+                )),
+            },
+        }
+    }
+
+    /// The limit of the value itself, `L` or `L` with the byte order
+    /// specified by `#[radiation(endian = "...")]` and the modes.
+    fn limit(&self) -> syn::Result<proc_macro2::TokenStream> {
+        let se = quote::quote!(radiation);
+        let limit = match self.value("endian") {
+            None => quote::quote!(L),
+            Some(value) => match syn::parse2::<syn::LitStr>(value.clone())?.value().as_str() {
+                "little" => quote::quote!(#se::LittleEndian<L>),
+                "big" => quote::quote!(#se::BigEndian<L>),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        value,
+                        "expected \"little\" or \"big\"",
+                    ))
+                }
+            },
+        };
+        self.wrap_mode(limit)
+    }
+}
+
+/// The limit of each field, `#[limit(...)]` attribute or the next limit
+/// after the previous field, wrapped by `#[radiation(prefix = ..., strict, canonical)]`.
+fn field_limits(
+    bindings: &[synstructure::BindingInfo],
+    limit_self: &proc_macro2::TokenStream,
+) -> syn::Result<Vec<proc_macro2::TokenStream>> {
+    let se = quote::quote!(radiation);
+    let mut limit_next = limit_self.clone();
+    let mut limits = vec![];
+    for binding in bindings {
+        let ast = binding.ast();
+        let limit = match find_attr(&ast.attrs, "limit") {
+            Some(attr) => {
+                let limit = attr.parse_args::<proc_macro2::TokenStream>()?;
+                quote::quote!(#se::Inherit<#limit, #limit_self>)
+            }
+            None => limit_next.clone(),
+        };
+        limit_next = quote::quote!(<#limit as #se::Limit>::Next);
+        let attr = RadiationAttr::new(&ast.attrs)?;
+        let limit = match attr.value("prefix") {
+            Some(prefix) => quote::quote!(#se::WithPrefix<#prefix, #limit>),
+            None => limit,
+        };
+        limits.push(attr.wrap_mode(limit)?);
+    }
+    Ok(limits)
+}
+
+/// Removes `#[radiation(skip)]` fields from the bindings, they are not on the wire,
+/// see `skipped_value`.
+fn skip_fields(s: &mut synstructure::Structure) -> syn::Result<()> {
+    for variant in s.variants() {
+        for binding in variant.bindings() {
+            let attr = RadiationAttr::new(&binding.ast().attrs)?;
+            if attr.has("default") && !attr.has("skip") {
+                let msg = "`default` requires `skip`";
+                return Err(syn::Error::new_spanned(binding.ast(), msg));
+            }
+        }
+    }
+    s.filter(|binding| {
+        !RadiationAttr::new(&binding.ast().attrs).map_or(false, |attr| attr.has("skip"))
+    });
+    Ok(())
+}
+
+/// The value of the skipped field, `#[radiation(default = ...)]` or `Default::default()`.
+fn skipped_value(field: &syn::Field) -> Option<proc_macro2::TokenStream> {
+    let attr = RadiationAttr::new(&field.attrs).ok()?;
+    if !attr.has("skip") {
+        return None;
+    }
+    Some(match attr.value("default") {
+        Some(value) => value.clone(),
+        None => quote::quote!(::core::default::Default::default()),
+    })
+}
+
+/// The catch-all `#[radiation(other)]` variant, the first field is the unknown tag,
+/// the second is the payload, absorbed and emitted by its own encoding.
+fn is_other(variant: &synstructure::VariantInfo) -> syn::Result<bool> {
+    if !RadiationAttr::new(variant.ast().attrs)?.has("other") {
+        Ok(false)
+    } else if variant.bindings().len() != 2 {
+        let msg = "the `other` variant has two fields, the tag and the payload";
+        Err(syn::Error::new_spanned(variant.ast().ident, msg))
+    } else {
+        Ok(true)
+    }
+}
+
+/// The presence of the field in the versioned layout.
+#[derive(Clone, Copy)]
+struct Presence {
+    /// `#[radiation(since = N)]`, present if the version is `N` or later.
+    since: Option<u64>,
+    /// `#[radiation(trailing_optional)]`, present if the input does not end.
+    trailing: bool,
+}
+
+impl Presence {
+    fn optional(&self) -> bool {
+        self.since.is_some() || self.trailing
+    }
+}
+
+/// The layout of the variant which evolves by appending fields, the optional
+/// fields are `Option<T>`. The version is the `#[radiation(version)]` field
+/// which precedes the `since` fields, or `L::VERSION`.
+struct Layout {
+    version: Option<usize>,
+    fields: Vec<Presence>,
+}
+
+impl Layout {
+    fn new(bindings: &[synstructure::BindingInfo]) -> syn::Result<Self> {
+        let mut version = None;
+        let mut fields = vec![];
+        for (i, binding) in bindings.iter().enumerate() {
+            let ast = binding.ast();
+            let attr = RadiationAttr::new(&ast.attrs)?;
+            let since = match (attr.has("since"), attr.value("since")) {
+                (false, _) => None,
+                (true, None) => {
+                    return Err(syn::Error::new_spanned(ast, "expected `since = N`"));
+                }
+                (true, Some(value)) => {
+                    Some(syn::parse2::<syn::LitInt>(value.clone())?.base10_parse::<u64>()?)
+                }
+            };
+            let trailing = attr.has("trailing_optional");
+            let presence = Presence { since, trailing };
+            if attr.has("version") {
+                if version.is_some() || presence.optional() {
+                    let msg = "expected a single `version` field which is not optional";
+                    return Err(syn::Error::new_spanned(ast, msg));
+                }
+                if fields.iter().any(|f: &Presence| f.since.is_some()) {
+                    let msg = "the `version` field precedes the `since` fields";
+                    return Err(syn::Error::new_spanned(ast, msg));
+                }
+                version = Some(i);
+            }
+            if !trailing && fields.iter().any(|f| f.trailing) {
+                let msg = "expected `trailing_optional`, the field follows the trailing optional";
+                return Err(syn::Error::new_spanned(ast, msg));
+            }
+            fields.push(presence);
+        }
+        Ok(Layout { version, fields })
+    }
+
+    fn versioned(&self) -> bool {
+        self.fields.iter().any(|f| f.since.is_some())
+    }
+
+    /// The condition of the presence of the optional field when absorbing.
+    fn condition(&self, i: usize) -> Option<proc_macro2::TokenStream> {
+        let Presence { since, trailing } = self.fields[i];
+        let since = since.map(|n| quote::quote!(version >= #n));
+        let trailing = trailing.then(|| quote::quote!(!input.is_empty()));
+        match (since, trailing) {
+            (None, None) => None,
+            (Some(c), None) | (None, Some(c)) => Some(c),
+            (Some(since), Some(trailing)) => Some(quote::quote!(#since && #trailing)),
+        }
+    }
+}
+
 macro_rules! extract_attr {
     ($attrs:expr, $name:expr) => {
         match find_attr($attrs, $name) {
@@ -34,7 +297,7 @@ impl<'a> Tags<'a> {
         structure: &'a synstructure::Structure<'a>,
     ) -> Result<(Self, proc_macro2::TokenStream), syn::Error> {
         let default_ty = if structure.variants().len() == 1 {
-            quote::quote!(<()>)
+            quote::quote!(())
         } else {
             quote::quote!(u16)
         };
@@ -43,7 +306,18 @@ impl<'a> Tags<'a> {
             None => default_ty,
         };
 
-        let current = quote::quote!(#ty::default());
+        let mut others = 0;
+        for variant in structure.variants() {
+            if is_other(variant)? {
+                others += 1;
+                if others > 1 || !matches!(structure.ast().data, syn::Data::Enum(_)) {
+                    let msg = "expected a single `other` variant of the enum";
+                    return Err(syn::Error::new_spanned(variant.ast().ident, msg));
+                }
+            }
+        }
+
+        let current = quote::quote!(<#ty>::default());
 
         Ok((
             Tags {
@@ -72,7 +346,10 @@ impl<'a> Iterator for Tags<'a> {
                 },
                 None => self.current.clone(),
             };
-            self.current = quote::quote!(#tag_val + 1);
+            // the `other` variant has no tag of its own
+            if !is_other(variant).unwrap_or_default() {
+                self.current = quote::quote!(#tag_val + 1);
+            }
 
             Some(Ok((tag_val, variant)))
         }
@@ -80,10 +357,13 @@ impl<'a> Iterator for Tags<'a> {
 }
 
 mod absorb;
-decl_derive!([Absorb, attributes(custom_absorb, as_str, limit, tag)] => absorb::derive);
+decl_derive!([Absorb, attributes(custom_absorb, as_str, limit, tag, radiation)] => absorb::derive);
 
 mod emit;
-decl_derive!([Emit, attributes(custom_emit, as_str, tag)] => emit::derive);
+decl_derive!([Emit, attributes(custom_emit, as_str, limit, tag, radiation)] => emit::derive);
 
 mod limit;
 decl_derive!([Limit, attributes(limit)] => limit::derive);
+
+mod canonical;
+decl_derive!([IsCanonical] => canonical::derive);
@@ -38,7 +38,7 @@ pub fn derive(s: synstructure::Structure) -> proc_macro2::TokenStream {
             loop {
                 if input.peek(Ident::peek_any) {
                     let ident = input.parse::<Ident>()?.to_string();
-                    let _ = input.parse::<Token![=]>()?;
+                    let _ = input.parse::<syn::Token![=]>()?;
                     match ident.as_str() {
                         "inner" => s.inner = input.parse()?,
                         "next" => s.next = input.parse()?,
@@ -50,10 +50,10 @@ pub fn derive(s: synstructure::Structure) -> proc_macro2::TokenStream {
                             return Err(syn::Error::new(ident.span(), msg));
                         }
                     }
-                    if !input.peek(Token![,]) {
+                    if !input.peek(syn::Token![,]) {
                         break;
                     } else {
-                        input.parse::<Token![,]>()?;
+                        input.parse::<syn::Token![,]>()?;
                     }
                 } else {
                     break;
@@ -1,35 +1,58 @@
 // Copyright 2022 Vladislav Melnik
 // SPDX-License-Identifier: MIT
 
-use core::sync::atomic::{AtomicUsize, AtomicU64, AtomicI64};
+use core::sync::atomic::{
+    AtomicBool, AtomicU8, AtomicU16, AtomicU32, AtomicU64, AtomicUsize, AtomicI8, AtomicI16,
+    AtomicI32, AtomicI64, AtomicIsize,
+};
 
-use nom::{IResult, combinator};
+use nom::{IResult, combinator, error::ErrorKind};
 
-use super::{core::Absorb, error::ParseError, limit::Limit};
+use super::{
+    core::Absorb,
+    error::{ParseError, ParseErrorKind},
+    limit::Limit,
+};
 
-impl<'pa> Absorb<'pa> for AtomicUsize {
-    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
-    where
-        L: Limit,
-    {
-        combinator::map(Absorb::absorb::<L>, |v| Self::new(v))(input)
-    }
+macro_rules! impl_atomic {
+    ($($t:ty),+) => {
+        $(
+            impl<'pa> Absorb<'pa> for $t {
+                fn absorb<L>(
+                    input: &'pa [u8],
+                ) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+                where
+                    L: Limit,
+                {
+                    combinator::map(Absorb::absorb::<L>, Self::new)(input)
+                }
+            }
+        )+
+    };
 }
 
-impl<'pa> Absorb<'pa> for AtomicU64 {
-    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
-    where
-        L: Limit,
-    {
-        combinator::map(Absorb::absorb::<L>, |v| Self::new(v))(input)
-    }
-}
+impl_atomic!(
+    AtomicBool,
+    AtomicU8,
+    AtomicU16,
+    AtomicU32,
+    AtomicU64,
+    AtomicUsize,
+    AtomicI8,
+    AtomicI16,
+    AtomicI32,
+    AtomicI64
+);
 
-impl<'pa> Absorb<'pa> for AtomicI64 {
+// encoded as `i64`, the size of `isize` depends on the platform
+impl<'pa> Absorb<'pa> for AtomicIsize {
     fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
     where
         L: Limit,
     {
-        combinator::map(Absorb::absorb::<L>, |v| Self::new(v))(input)
+        let (rest, v) = i64::absorb::<L>(input)?;
+        let v = isize::try_from(v)
+            .map_err(|_| ParseErrorKind::Nom(ErrorKind::TooLarge).error(input))?;
+        Ok((rest, Self::new(v)))
     }
 }
@@ -0,0 +1,145 @@
+// Copyright 2022 Vladislav Melnik
+// SPDX-License-Identifier: MIT
+
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+#[cfg(feature = "std")]
+use std::{
+    collections::{HashMap, HashSet},
+    hash::{Hash, BuildHasher},
+};
+
+use nom::{IResult, combinator, multi};
+
+use super::{
+    core::{Absorb, complete},
+    error::{ParseError, ParseErrorKind},
+    limit::Limit,
+    seq::fold_parser_with,
+};
+#[cfg(feature = "std")]
+use super::seq::canonical_order;
+
+/// The same encoding as `Vec<T>`, the length in bytes and the elements,
+/// `L` limits the length, the elements take `L::Inner`.
+fn length_prefixed<'pa, C, T, L, F>(
+    insert: F,
+) -> impl FnMut(&'pa [u8]) -> IResult<&'pa [u8], C, ParseError<&'pa [u8]>>
+where
+    L: Limit,
+    C: Default,
+    T: Absorb<'pa>,
+    F: FnMut(&mut C, T, &'pa [u8]) -> Result<(), ParseErrorKind>,
+{
+    combinator::map_parser(
+        multi::length_data(usize::absorb::<L>),
+        combinator::all_consuming(complete(fold_parser_with::<C, T, L, F>(insert))),
+    )
+}
+
+/// In strict mode the key must be greater than the previous one.
+fn check_order<K, L>(last: Option<&K>, key: &K) -> Result<(), ParseErrorKind>
+where
+    K: Ord,
+    L: Limit,
+{
+    match last {
+        Some(last) if L::STRICT && *last == *key => Err(ParseErrorKind::DuplicateKey),
+        Some(last) if L::STRICT && *last > *key => Err(ParseErrorKind::UnsortedKey),
+        _ => Ok(()),
+    }
+}
+
+impl<'pa, T> Absorb<'pa> for VecDeque<T>
+where
+    T: Absorb<'pa>,
+{
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        length_prefixed::<_, T, L, _>(|acc: &mut Self, value, _| {
+            acc.push_back(value);
+            Ok(())
+        })(input)
+    }
+}
+
+impl<'pa, K, V> Absorb<'pa> for BTreeMap<K, V>
+where
+    K: Absorb<'pa> + Ord,
+    V: Absorb<'pa>,
+{
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        length_prefixed::<_, (K, V), L, _>(|acc: &mut Self, (key, value), _| {
+            check_order::<K, L>(acc.keys().next_back(), &key)?;
+            acc.insert(key, value);
+            Ok(())
+        })(input)
+    }
+}
+
+impl<'pa, T> Absorb<'pa> for BTreeSet<T>
+where
+    T: Absorb<'pa> + Ord,
+{
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        length_prefixed::<_, T, L, _>(|acc: &mut Self, value, _| {
+            check_order::<T, L>(acc.iter().next_back(), &value)?;
+            acc.insert(value);
+            Ok(())
+        })(input)
+    }
+}
+
+// the order of the hash collection is arbitrary, strict mode rejects only duplicates,
+// canonical mode requires the entries sorted by their encoding
+
+#[cfg(feature = "std")]
+impl<'pa, K, V, S> Absorb<'pa> for HashMap<K, V, S>
+where
+    K: Absorb<'pa> + Hash + Eq,
+    V: Absorb<'pa>,
+    S: BuildHasher + Default,
+{
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        let mut last = None;
+        length_prefixed::<_, (K, V), L, _>(move |acc: &mut Self, (key, value), encoded| {
+            canonical_order::<L>(&mut last, encoded)?;
+            match acc.insert(key, value) {
+                Some(_) if L::STRICT => Err(ParseErrorKind::DuplicateKey),
+                _ => Ok(()),
+            }
+        })(input)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'pa, T, S> Absorb<'pa> for HashSet<T, S>
+where
+    T: Absorb<'pa> + Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        let mut last = None;
+        length_prefixed::<_, T, L, _>(move |acc: &mut Self, value, encoded| {
+            canonical_order::<L>(&mut last, encoded)?;
+            if !acc.insert(value) && L::STRICT {
+                Err(ParseErrorKind::DuplicateKey)
+            } else {
+                Ok(())
+            }
+        })(input)
+    }
+}
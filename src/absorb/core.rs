@@ -1,10 +1,21 @@
 // Copyright 2022 Vladislav Melnik
 // SPDX-License-Identifier: MIT
 
-use nom::{IResult, Err};
+use nom::{IResult, Err, error::ErrorKind};
 
-use super::{limit::Limit, error::ParseError};
+use super::{
+    limit::{Limit, Canonical},
+    error::{ParseError, ParseErrorKind},
+    primitives::absorb_array,
+};
 
+/// Parse the value from the beginning of the input.
+///
+/// The input may be a prefix of the encoded value, in this case
+/// the implementation returns `Err::Incomplete` with the number of bytes
+/// it needs to make progress. Inside a region whose size is known
+/// (e.g. the content of `Vec<T>` or `DynSized<T>`) a lack of bytes
+/// is a corruption and results in `Err::Error`.
 pub trait Absorb<'pa>
 where
     Self: Sized,
@@ -12,13 +23,35 @@ where
     fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
     where
         L: Limit;
+
+    /// The array `[Self; S]`, the elements one after another,
+    /// `u8` takes all the bytes at once.
+    #[doc(hidden)]
+    fn absorb_array<L, const S: usize>(
+        input: &'pa [u8],
+    ) -> IResult<&'pa [u8], [Self; S], ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        absorb_array::<Self, L, S>(input)
+    }
 }
 
 pub trait AbsorbExt<'pa>
 where
     Self: Sized,
 {
+    /// The input must contain the whole value,
+    /// a lack of bytes results in `ParseErrorKind::Nom(ErrorKind::Eof)`.
     fn absorb_ext(input: &'pa [u8]) -> Result<Self, Err<ParseError<&'pa [u8]>>>;
+
+    /// The input may be a prefix of the encoded value, returns the value
+    /// and the number of consumed bytes, or `Err::Incomplete`
+    /// if more bytes are needed.
+    fn absorb_streaming(input: &'pa [u8]) -> Result<(Self, usize), Err<ParseError<&'pa [u8]>>>;
+
+    /// Like `absorb_ext`, but rejects the encoding which is not canonical.
+    fn absorb_canonical(input: &'pa [u8]) -> Result<Self, Err<ParseError<&'pa [u8]>>>;
 }
 
 impl<'pa, T> AbsorbExt<'pa> for T
@@ -26,6 +59,30 @@ where
     T: Absorb<'pa>,
 {
     fn absorb_ext(input: &'pa [u8]) -> Result<Self, Err<ParseError<&'pa [u8]>>> {
-        T::absorb::<()>(input).map(|(_, t)| t)
+        complete(T::absorb::<()>)(input).map(|(_, t)| t)
+    }
+
+    fn absorb_streaming(input: &'pa [u8]) -> Result<(Self, usize), Err<ParseError<&'pa [u8]>>> {
+        T::absorb::<()>(input).map(|(remaining, t)| (t, input.len() - remaining.len()))
+    }
+
+    fn absorb_canonical(input: &'pa [u8]) -> Result<Self, Err<ParseError<&'pa [u8]>>> {
+        complete(T::absorb::<Canonical>)(input).map(|(_, t)| t)
+    }
+}
+
+/// Treat the input as the whole region, turns `Err::Incomplete` into an error
+/// pointing at the end of the region.
+pub(super) fn complete<'pa, O, F>(
+    mut f: F,
+) -> impl FnMut(&'pa [u8]) -> IResult<&'pa [u8], O, ParseError<&'pa [u8]>>
+where
+    F: FnMut(&'pa [u8]) -> IResult<&'pa [u8], O, ParseError<&'pa [u8]>>,
+{
+    move |input| match f(input) {
+        Err(Err::Incomplete(_)) => {
+            Err(ParseErrorKind::Nom(ErrorKind::Eof).error(&input[input.len()..]))
+        }
+        r => r,
     }
 }
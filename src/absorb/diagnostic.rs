@@ -0,0 +1,132 @@
+// Copyright 2022 Vladislav Melnik
+// SPDX-License-Identifier: MIT
+
+use core::fmt;
+
+use super::error::ParseError;
+
+const ROW: usize = 16;
+
+/// Renders the error with a hexdump of the `original` buffer around
+/// the failing byte, see `ParseError::diagnostic`.
+///
+/// ```text
+/// error: Limited.big.one: LimitOne, falls outside the allowed maximum, 28 > 24
+///   at offset 10 (0xa) of 40 bytes
+/// 00000000  01 41 00 00 00 22 00 00  00 1c[00]01 23 45 00 01  |.A..."......#E..|
+/// 00000010  23 45 00 01 23 45 00 01  23 45 00 01 23 45 00 01  |#E..#E..#E..#E..|
+/// ```
+pub struct Diagnostic<'a, I> {
+    error: &'a ParseError<I>,
+    original: &'a [u8],
+    context: usize,
+}
+
+impl<I> ParseError<I>
+where
+    I: AsRef<[u8]>,
+{
+    /// The diagnostic renderer, implements `Display`,
+    /// shows two rows of the hexdump before and after the failing one.
+    pub fn diagnostic<'a>(&'a self, original: &'a [u8]) -> Diagnostic<'a, I> {
+        Diagnostic {
+            error: self,
+            original,
+            context: 2,
+        }
+    }
+}
+
+impl<'a, I> Diagnostic<'a, I> {
+    /// The number of rows of 16 bytes before and after the failing one.
+    pub fn context(mut self, rows: usize) -> Self {
+        self.context = rows;
+        self
+    }
+}
+
+impl<'a, I> Diagnostic<'a, I>
+where
+    I: AsRef<[u8]>,
+{
+    fn line(f: &mut fmt::Formatter<'_>, prefix: &str, error: &ParseError<I>) -> fmt::Result {
+        write!(f, "{prefix}: ")?;
+        if !error.path.is_empty() {
+            error.write_path(f)?;
+            write!(f, ": ")?;
+        }
+        writeln!(f, "{}", error.kind)
+    }
+
+    fn position(&self, f: &mut fmt::Formatter<'_>, error: &ParseError<I>) -> fmt::Result {
+        let len = self.original.len();
+        match error.offset(self.original) {
+            Some(offset) => writeln!(f, "  at offset {offset} ({offset:#x}) of {len} bytes"),
+            None => writeln!(f, "  the input is outside of the original buffer"),
+        }
+    }
+
+    fn hexdump(&self, f: &mut fmt::Formatter<'_>, offset: usize) -> fmt::Result {
+        let failing = offset / ROW;
+        // the error at the end of the buffer takes an empty row
+        let rows = (self.original.len() / ROW).max(failing) + 1;
+        let first = failing.saturating_sub(self.context);
+        let last = (failing + self.context + 1).min(rows);
+        for row in first..last {
+            let start = row * ROW;
+            let bytes = self.original.get(start..).unwrap_or_default();
+            let bytes = &bytes[..bytes.len().min(ROW)];
+            if bytes.is_empty() && start != offset {
+                break;
+            }
+            write!(f, "{start:08x} ")?;
+            for i in 0..ROW {
+                let gap = if i == ROW / 2 { "  " } else { " " };
+                // the brackets take the place of the gaps around the failing byte
+                let gap = if start + i == offset || start + i == offset + 1 {
+                    &gap[1..]
+                } else {
+                    gap
+                };
+                match bytes.get(i) {
+                    Some(byte) if start + i == offset => write!(f, "{gap}[{byte:02x}]")?,
+                    Some(byte) => write!(f, "{gap}{byte:02x}")?,
+                    None if start + i == offset => write!(f, "{gap}[  ]")?,
+                    None => write!(f, "{gap}  ")?,
+                }
+            }
+            let gap = if start + ROW == offset + 1 { " " } else { "  " };
+            write!(f, "{gap}|")?;
+            for byte in bytes {
+                let c = if byte.is_ascii_graphic() || *byte == b' ' {
+                    char::from(*byte)
+                } else {
+                    '.'
+                };
+                write!(f, "{c}")?;
+            }
+            writeln!(f, "|")?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, I> fmt::Display for Diagnostic<'a, I>
+where
+    I: AsRef<[u8]>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Self::line(f, "error", self.error)?;
+        self.position(f, self.error)?;
+        if let Some(offset) = self.error.offset(self.original) {
+            self.hexdump(f, offset)?;
+        }
+        let mut subsequent = self.error.subsequent.as_deref();
+        while let Some(error) = subsequent {
+            Self::line(f, "caused by", error)?;
+            self.position(f, error)?;
+            subsequent = error.subsequent.as_deref();
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
 // Copyright 2022 Vladislav Melnik
 // SPDX-License-Identifier: MIT
 
-use core::fmt;
+use core::{any::Any, fmt};
 use alloc::{
     string::{String, ToString},
     boxed::Box,
@@ -10,17 +10,121 @@ use alloc::{
 
 use nom::{
     error::{ParseError as NomParseError, ErrorKind as NomErrorKind, FromExternalError},
-    Err,
+    Err, IResult,
 };
 
 use super::limit::LimitError;
 
+/// The value of the unknown tag, may be downcast to the type of the tag.
+pub trait TagValue: Any + fmt::Debug + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T> TagValue for T
+where
+    T: Any + fmt::Debug + Send + Sync,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl dyn TagValue {
+    pub fn downcast_ref<T>(&self) -> Option<&T>
+    where
+        T: Any,
+    {
+        self.as_any().downcast_ref()
+    }
+}
+
+/// The error of a custom parser, e.g. `FromStr::Err` of `#[as_str]` field,
+/// the message, and the error itself if the parser keeps it,
+/// see `ParseError::custom`.
+pub struct Source {
+    message: String,
+    #[cfg(any(feature = "std", feature = "codec"))]
+    error: Option<Box<dyn std::error::Error + Send + Sync>>,
+}
+
+impl Source {
+    pub fn new<E>(e: E) -> Self
+    where
+        E: fmt::Display,
+    {
+        Source {
+            message: e.to_string(),
+            #[cfg(any(feature = "std", feature = "codec"))]
+            error: None,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The error of the parser, if it is kept.
+    #[cfg(any(feature = "std", feature = "codec"))]
+    pub fn error(&self) -> Option<&(dyn std::error::Error + Send + Sync + 'static)> {
+        self.error.as_deref()
+    }
+}
+
+impl fmt::Debug for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.message, f)
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
 #[derive(Debug)]
 pub enum ParseErrorKind {
     Nom(NomErrorKind),
     Limit(LimitError, &'static str),
-    UnknownTag { tag: String, hint: &'static str },
-    Custom(NomErrorKind, String),
+    UnknownTag {
+        tag: Box<dyn TagValue>,
+        hint: &'static str,
+    },
+    /// The string is not UTF-8, the position is relative to the string.
+    InvalidUtf8 {
+        valid_up_to: usize,
+    },
+    /// The byte is neither `0x00` nor `0xff`.
+    InvalidBool(u8),
+    /// The discriminant of `Option` or `Result`, the hint is the type name.
+    InvalidDiscriminant {
+        value: u8,
+        hint: &'static str,
+    },
+    /// The element of the collection takes zero bytes, the collection would be infinite.
+    ZeroSizedLoop,
+    /// The variable length integer has redundant trailing zero groups.
+    Overlong,
+    /// The value is not a Unicode scalar value.
+    InvalidChar(u32),
+    /// Zero value of `NonZero*` integer, the hint is the type name.
+    Zero(&'static str),
+    /// The key is already in the collection, see `Limit::STRICT`.
+    DuplicateKey,
+    /// The key is less than the previous one, see `Limit::STRICT`.
+    UnsortedKey,
+    /// The value has another encoding which is canonical, see `Limit::CANONICAL`.
+    NotCanonical(&'static str),
+    /// The nanoseconds of `Duration` or `SystemTime` are not less than a second.
+    InvalidNanos(u32),
+    /// The time does not fit `SystemTime` of the platform.
+    TimeOverflow,
+    /// The path or `OsString` is not UTF-8, which is required in strict mode,
+    /// or on the platform other than Unix, the position is relative to the string.
+    NonUtf8Path {
+        valid_up_to: usize,
+    },
+    Custom(NomErrorKind, Source),
 }
 
 impl fmt::Display for ParseErrorKind {
@@ -28,7 +132,26 @@ impl fmt::Display for ParseErrorKind {
         match self {
             ParseErrorKind::Nom(err) => write!(f, "{err:?}"),
             ParseErrorKind::Limit(err, hint) => write!(f, "{hint}, {err}"),
-            ParseErrorKind::UnknownTag { tag, hint } => write!(f, "unknown tag: {tag}, {hint}"),
+            ParseErrorKind::UnknownTag { tag, hint } => write!(f, "unknown tag: {tag:?}, {hint}"),
+            ParseErrorKind::InvalidUtf8 { valid_up_to } => {
+                write!(f, "invalid utf-8 after {valid_up_to} bytes")
+            }
+            ParseErrorKind::InvalidBool(v) => write!(f, "invalid bool: {v:#04x}"),
+            ParseErrorKind::InvalidDiscriminant { value, hint } => {
+                write!(f, "invalid discriminant: {value:#04x}, {hint}")
+            }
+            ParseErrorKind::ZeroSizedLoop => write!(f, "zero sized infinite loop"),
+            ParseErrorKind::Overlong => write!(f, "overlong variable length integer"),
+            ParseErrorKind::InvalidChar(v) => write!(f, "invalid unicode scalar value: {v:#x}"),
+            ParseErrorKind::Zero(hint) => write!(f, "zero value of {hint}"),
+            ParseErrorKind::DuplicateKey => write!(f, "duplicate key"),
+            ParseErrorKind::UnsortedKey => write!(f, "unsorted key"),
+            ParseErrorKind::NotCanonical(hint) => write!(f, "not canonical, {hint}"),
+            ParseErrorKind::InvalidNanos(v) => write!(f, "invalid nanoseconds: {v}"),
+            ParseErrorKind::TimeOverflow => write!(f, "time overflow"),
+            ParseErrorKind::NonUtf8Path { valid_up_to } => {
+                write!(f, "non utf-8 path after {valid_up_to} bytes")
+            }
             ParseErrorKind::Custom(err, custom) => write!(f, "{err:?}, custom: {custom}"),
         }
     }
@@ -37,19 +160,28 @@ impl fmt::Display for ParseErrorKind {
 impl ParseErrorKind {
     pub fn unknown_tag<T>(tag: T, hint: &'static str) -> Self
     where
-        T: fmt::Debug,
+        T: TagValue,
     {
         ParseErrorKind::UnknownTag {
-            tag: format!("{tag:?}"),
+            tag: Box::new(tag),
             hint,
         }
     }
 
+    /// The error of the custom parser.
+    pub fn source(&self) -> Option<&Source> {
+        match self {
+            ParseErrorKind::Custom(_, source) => Some(source),
+            _ => None,
+        }
+    }
+
     pub fn error<I>(self, input: I) -> Err<ParseError<I>> {
         Err::Error(ParseError {
             input,
             kind: self,
             subsequent: None,
+            path: Vec::new(),
         })
     }
 
@@ -58,15 +190,30 @@ impl ParseErrorKind {
     }
 }
 
+/// The element of the path to the value which failed, see `ParseError::path`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    Type(&'static str),
+    Variant(&'static str),
+    Field(&'static str),
+    Index(usize),
+}
+
 pub struct ParseError<I> {
     pub input: I,
     pub kind: ParseErrorKind,
     pub subsequent: Option<Box<ParseError<I>>>,
+    /// The innermost segment goes first.
+    pub path: Vec<PathSegment>,
 }
 
 impl<I> fmt::Display for ParseError<I> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let kind = &self.kind;
+        if !self.path.is_empty() {
+            self.write_path(f)?;
+            write!(f, ": ")?;
+        }
         match &self.subsequent {
             None => write!(f, "kind: {kind}"),
             Some(ref subsequent) => write!(f, "kind: {kind}, subsequent: ({subsequent})"),
@@ -74,6 +221,95 @@ impl<I> fmt::Display for ParseError<I> {
     }
 }
 
+impl<I> ParseError<I> {
+    /// The error of a custom parser which keeps the error itself,
+    /// unlike `FromExternalError` (e.g. `map_res`) which keeps the message.
+    #[cfg(any(feature = "std", feature = "codec"))]
+    pub fn custom<E>(input: I, kind: NomErrorKind, e: E) -> Self
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let error = e.into();
+        ParseError {
+            input,
+            kind: ParseErrorKind::Custom(
+                kind,
+                Source {
+                    message: error.to_string(),
+                    error: Some(error),
+                },
+            ),
+            subsequent: None,
+            path: Vec::new(),
+        }
+    }
+
+    pub fn push(mut self, segment: PathSegment) -> Self {
+        self.path.push(segment);
+        self
+    }
+
+    /// The path like `Limited.big.one[3]`, only the outermost type is named.
+    pub fn path_string(&self) -> String {
+        let mut s = String::new();
+        let _ = self.write_path(&mut s);
+        s
+    }
+
+    /// Writes the path without allocation, see `path_string`.
+    pub(super) fn write_path<W>(&self, w: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        let mut named = false;
+        for segment in self.path.iter().rev() {
+            match segment {
+                PathSegment::Type(name) if !named => write!(w, "{name}")?,
+                PathSegment::Type(_) => (),
+                PathSegment::Variant(name) => write!(w, "::{name}")?,
+                PathSegment::Field(name) => write!(w, ".{name}")?,
+                PathSegment::Index(index) => write!(w, "[{index}]")?,
+            }
+            named = true;
+        }
+        Ok(())
+    }
+}
+
+impl<I> ParseError<I>
+where
+    I: AsRef<[u8]>,
+{
+    /// The position of the error in the `original` buffer, `None` if the input
+    /// of the error does not point inside of it.
+    pub fn offset(&self, original: &[u8]) -> Option<usize> {
+        let start = original.as_ptr() as usize;
+        let position = self.input.as_ref().as_ptr() as usize;
+        position
+            .checked_sub(start)
+            .filter(|offset| *offset <= original.len())
+    }
+
+    /// Like `Display`, with the offset in the `original` buffer.
+    pub fn report(&self, original: &[u8]) -> String {
+        match self.offset(original) {
+            Some(offset) => format!("{self} at offset {offset}"),
+            None => self.to_string(),
+        }
+    }
+}
+
+/// Push the `segment` to the path of the error returned by `f`.
+pub fn with_path<I, O, F>(
+    segment: PathSegment,
+    mut f: F,
+) -> impl FnMut(I) -> IResult<I, O, ParseError<I>>
+where
+    F: FnMut(I) -> IResult<I, O, ParseError<I>>,
+{
+    move |input| f(input).map_err(|err| err.map(|err| err.push(segment.clone())))
+}
+
 impl<I> fmt::Debug for ParseError<I>
 where
     I: AsRef<[u8]>,
@@ -82,11 +318,15 @@ where
         f.debug_struct("Error")
             .field("kind", &self.kind)
             .field("subsequent", &self.subsequent)
+            .field("path", &self.path)
             .field("input", &hex::encode(&self.input))
             .finish()
     }
 }
 
+/// The error which does not borrow the input, see `ParseError::into_vec`.
+pub type OwnedParseError = ParseError<Vec<u8>>;
+
 impl<I> ParseError<I>
 where
     I: Into<Vec<u8>>,
@@ -96,16 +336,42 @@ where
             input: self.input.into(),
             kind: self.kind,
             subsequent: self.subsequent.map(|e| Box::new(ParseError::into_vec(*e))),
+            path: self.path,
         }
     }
 }
 
+#[cfg(any(feature = "std", feature = "codec"))]
+impl<I> std::error::Error for ParseError<I>
+where
+    I: AsRef<[u8]> + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.subsequent {
+            Some(subsequent) => Some(&**subsequent),
+            None => self
+                .kind
+                .source()
+                .and_then(Source::error)
+                .map(|error| error as _),
+        }
+    }
+}
+
+#[cfg(any(feature = "std", feature = "codec"))]
+impl From<OwnedParseError> for std::io::Error {
+    fn from(err: OwnedParseError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+    }
+}
+
 impl<I> NomParseError<I> for ParseError<I> {
     fn from_error_kind(input: I, kind: NomErrorKind) -> Self {
         ParseError {
             input,
             kind: ParseErrorKind::Nom(kind),
             subsequent: None,
+            path: Vec::new(),
         }
     }
 
@@ -114,6 +380,7 @@ impl<I> NomParseError<I> for ParseError<I> {
             input,
             kind: ParseErrorKind::Nom(kind),
             subsequent: Some(Box::new(other)),
+            path: Vec::new(),
         }
     }
 }
@@ -125,8 +392,9 @@ where
     fn from_external_error(input: I, kind: NomErrorKind, e: E) -> Self {
         ParseError {
             input,
-            kind: ParseErrorKind::Custom(kind, e.to_string()),
+            kind: ParseErrorKind::Custom(kind, Source::new(e)),
             subsequent: None,
+            path: Vec::new(),
         }
     }
 }
@@ -1,7 +1,11 @@
 // Copyright 2022 Vladislav Melnik
 // SPDX-License-Identifier: MIT
 
-use core::fmt;
+use core::{fmt, marker::PhantomData, sync::atomic::Ordering};
+
+use nom::number::Endianness;
+
+use super::VarU64;
 
 pub struct LimitError {
     minimum: usize,
@@ -32,6 +36,72 @@ impl fmt::Display for LimitError {
     }
 }
 
+#[cfg(any(feature = "std", feature = "codec"))]
+impl std::error::Error for LimitError {}
+
+/// The encoding of the length of `Vec<T>`, `str`, `DynSized<T>` and other
+/// length prefixed values, and of `usize` itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthPrefix {
+    U8,
+    U16,
+    U32,
+    U64,
+    /// Unsigned LEB128, see `VarU64`.
+    Var,
+}
+
+impl LengthPrefix {
+    /// The maximal length the prefix can hold.
+    pub const fn maximum(self) -> u64 {
+        match self {
+            LengthPrefix::U8 => u8::MAX as u64,
+            LengthPrefix::U16 => u16::MAX as u64,
+            LengthPrefix::U32 => u32::MAX as u64,
+            LengthPrefix::U64 | LengthPrefix::Var => u64::MAX,
+        }
+    }
+
+    /// The number of bytes the prefix occupies if it holds the `length`.
+    pub const fn encoded_len(self, length: u64) -> usize {
+        match self {
+            LengthPrefix::U8 => 1,
+            LengthPrefix::U16 => 2,
+            LengthPrefix::U32 => 4,
+            LengthPrefix::U64 => 8,
+            LengthPrefix::Var => match length {
+                0 => 1,
+                _ => (64 - length.leading_zeros() as usize + 6) / 7,
+            },
+        }
+    }
+}
+
+/// The type which may be used as a length prefix, see `WithPrefix`.
+pub trait AsLengthPrefix {
+    const PREFIX: LengthPrefix;
+}
+
+impl AsLengthPrefix for u8 {
+    const PREFIX: LengthPrefix = LengthPrefix::U8;
+}
+
+impl AsLengthPrefix for u16 {
+    const PREFIX: LengthPrefix = LengthPrefix::U16;
+}
+
+impl AsLengthPrefix for u32 {
+    const PREFIX: LengthPrefix = LengthPrefix::U32;
+}
+
+impl AsLengthPrefix for u64 {
+    const PREFIX: LengthPrefix = LengthPrefix::U64;
+}
+
+impl AsLengthPrefix for VarU64 {
+    const PREFIX: LengthPrefix = LengthPrefix::Var;
+}
+
 pub trait Limit {
     type Inner: Limit;
 
@@ -43,6 +113,29 @@ pub trait Limit {
 
     const DESCRIPTION: &'static str;
 
+    /// The byte order of integers, length prefixes and enum tags.
+    const ENDIANNESS: Endianness = Endianness::Big;
+
+    /// The encoding of the length prefix, applies only to the value itself,
+    /// the inner and next limits specify their own.
+    const PREFIX: LengthPrefix = LengthPrefix::U32;
+
+    /// Reject the collection whose keys are duplicated or unsorted,
+    /// the encoding which is not canonical.
+    const STRICT: bool = false;
+
+    /// Accept and produce only the unique encoding of the value, implies `STRICT`.
+    const CANONICAL: bool = false;
+
+    /// The memory ordering of the loads of atomics when emitting,
+    /// `Relaxed`, `Acquire` or `SeqCst`, `Release` loads as `Relaxed`
+    /// and `AcqRel` as `Acquire`.
+    const ORDERING: Ordering = Ordering::Relaxed;
+
+    /// The version of the layout of the derived structure which has
+    /// no `#[radiation(version)]` field, the latest by default, see `Version`.
+    const VERSION: u64 = u64::MAX;
+
     fn check(size: usize) -> Result<usize, LimitError> {
         if size <= Self::UPPER && size >= Self::LOWER {
             Ok(size)
@@ -89,3 +182,148 @@ where
 
     const DESCRIPTION: &'static str = "wait for issue 44580 (adt_const_params)";
 }
+
+// forwards every item to `L` except the given ones,
+// the bounds and the prefix are taken from `bounds` if given
+macro_rules! impl_wrapper {
+    (@or ; $default:expr) => { $default };
+    (@or $value:expr; $default:expr) => { $value };
+    (@bounds) => { L };
+    (@bounds $x:ident) => { $x };
+    (
+        impl<$($g:ident),+ $(; const $c:ident: $ct:ty)?> $ty:ty $(where $wt:ident: $wb:path)? {
+            Inner = $inner:ty,
+            Next = $next:ty,
+            $(bounds = $x:ident,)?
+            $(ENDIANNESS = $endianness:expr,)?
+            $(PREFIX = $prefix:expr,)?
+            $(STRICT = $strict:expr,)?
+            $(CANONICAL = $canonical:expr,)?
+            $(ORDERING = $ordering:expr,)?
+            $(VERSION = $version:expr,)?
+        }
+    ) => {
+        impl<$(const $c: $ct,)? $($g),+> Limit for $ty
+        where
+            $($wt: $wb,)?
+            $($x: Limit,)?
+            L: Limit,
+        {
+            type Inner = $inner;
+
+            type Next = $next;
+
+            const LOWER: usize = <impl_wrapper!(@bounds $($x)?) as Limit>::LOWER;
+
+            const UPPER: usize = <impl_wrapper!(@bounds $($x)?) as Limit>::UPPER;
+
+            const DESCRIPTION: &'static str = <impl_wrapper!(@bounds $($x)?) as Limit>::DESCRIPTION;
+
+            const ENDIANNESS: Endianness = impl_wrapper!(@or $($endianness)?; L::ENDIANNESS);
+
+            const PREFIX: LengthPrefix = impl_wrapper!(@or $($prefix)?; <impl_wrapper!(@bounds $($x)?) as Limit>::PREFIX);
+
+            const STRICT: bool = impl_wrapper!(@or $($strict)?; L::STRICT);
+
+            const CANONICAL: bool = impl_wrapper!(@or $($canonical)?; L::CANONICAL);
+
+            const ORDERING: Ordering = impl_wrapper!(@or $($ordering)?; L::ORDERING);
+
+            const VERSION: u64 = impl_wrapper!(@or $($version)?; L::VERSION);
+
+            fn check(size: usize) -> Result<usize, LimitError> {
+                <impl_wrapper!(@bounds $($x)?) as Limit>::check(size)
+            }
+        }
+    };
+}
+
+/// The limit `L` with little endian byte order, applies to inner and next limits.
+pub struct LittleEndian<L = ()>(PhantomData<L>);
+
+impl_wrapper!(impl<L> LittleEndian<L> {
+    Inner = LittleEndian<L::Inner>,
+    Next = LittleEndian<L::Next>,
+    ENDIANNESS = Endianness::Little,
+});
+
+/// The limit `L` with big endian byte order, applies to inner and next limits.
+pub struct BigEndian<L = ()>(PhantomData<L>);
+
+impl_wrapper!(impl<L> BigEndian<L> {
+    Inner = BigEndian<L::Inner>,
+    Next = BigEndian<L::Next>,
+    ENDIANNESS = Endianness::Big,
+});
+
+/// The limit `L` which rejects duplicated or unsorted keys,
+/// applies to inner and next limits.
+pub struct Strict<L = ()>(PhantomData<L>);
+
+impl_wrapper!(impl<L> Strict<L> {
+    Inner = Strict<L::Inner>,
+    Next = Strict<L::Next>,
+    STRICT = true,
+});
+
+/// The limit `L` in canonical mode, applies to inner and next limits.
+/// Absorb rejects the non canonical encoding, e.g. NaN with a payload, emit
+/// normalizes the value and sorts the entries of hash collections.
+pub struct Canonical<L = ()>(PhantomData<L>);
+
+impl_wrapper!(impl<L> Canonical<L> {
+    Inner = Canonical<L::Inner>,
+    Next = Canonical<L::Next>,
+    STRICT = true,
+    CANONICAL = true,
+});
+
+/// The size constraints of `X` with the encoding options (byte order, strictness) of `L`.
+/// The derive macro uses it for `#[limit(X)]` fields, so the options
+/// of the outer value apply to the field.
+pub struct Inherit<X, L>(PhantomData<(X, L)>);
+
+impl_wrapper!(impl<X, L> Inherit<X, L> {
+    Inner = Inherit<X::Inner, L>,
+    Next = Inherit<X::Next, L>,
+    bounds = X,
+});
+
+/// The limit `L` with the length prefix encoded as `P`, e.g. `u8`.
+pub struct WithPrefix<P, L = ()>(PhantomData<(P, L)>);
+
+impl_wrapper!(impl<P, L> WithPrefix<P, L> where P: AsLengthPrefix {
+    Inner = L::Inner,
+    Next = L::Next,
+    PREFIX = P::PREFIX,
+});
+
+/// The limit `L` whose atomics are loaded with `Ordering::Acquire`,
+/// applies to inner and next limits.
+pub struct Acquire<L = ()>(PhantomData<L>);
+
+impl_wrapper!(impl<L> Acquire<L> {
+    Inner = Acquire<L::Inner>,
+    Next = Acquire<L::Next>,
+    ORDERING = Ordering::Acquire,
+});
+
+/// The limit `L` whose atomics are loaded with `Ordering::SeqCst`,
+/// applies to inner and next limits.
+pub struct SeqCst<L = ()>(PhantomData<L>);
+
+impl_wrapper!(impl<L> SeqCst<L> {
+    Inner = SeqCst<L::Inner>,
+    Next = SeqCst<L::Next>,
+    ORDERING = Ordering::SeqCst,
+});
+
+/// The limit `L` with the version `V` of the layout of derived structures,
+/// selects the `#[radiation(since = N)]` fields, applies to inner and next limits.
+pub struct Version<const V: u64, L = ()>(PhantomData<L>);
+
+impl_wrapper!(impl<L; const V: u64> Version<V, L> {
+    Inner = Version<V, L::Inner>,
+    Next = Version<V, L::Next>,
+    VERSION = V,
+});
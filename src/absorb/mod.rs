@@ -1,13 +1,21 @@
 // Copyright 2022 Vladislav Melnik
 // SPDX-License-Identifier: MIT
 
-use super::{DynSized, Collection};
+use super::{DynSized, Collection, Unordered, Le, Be, Prefixed, VarU64, VarI64};
 
 mod limit;
-pub use self::limit::{Limit, LimitDescriptor, LimitError};
+pub use self::limit::{
+    Limit, LimitDescriptor, LimitError, LittleEndian, BigEndian, Inherit, LengthPrefix,
+    AsLengthPrefix, WithPrefix, Strict, Canonical, Acquire, SeqCst, Version,
+};
 
 mod error;
-pub use self::error::{ParseError, ParseErrorKind};
+pub use self::error::{
+    ParseError, OwnedParseError, ParseErrorKind, PathSegment, TagValue, Source, with_path,
+};
+
+mod diagnostic;
+pub use self::diagnostic::Diagnostic;
 
 mod core;
 pub use self::core::{Absorb, AbsorbExt};
@@ -16,7 +24,17 @@ mod primitives;
 
 mod atomics;
 
+mod pointers;
+
+mod varint;
+
 mod seq;
+pub use self::seq::absorb_parsed;
+
+mod collections;
+
+mod view;
+pub use self::view::{SeqView, RestView, ViewIter};
 
 /// implementations for some standard types
 #[cfg(feature = "std")]
@@ -0,0 +1,140 @@
+// Copyright 2022 Vladislav Melnik
+// SPDX-License-Identifier: MIT
+
+use core::cell::{Cell, RefCell};
+use alloc::{boxed::Box, rc::Rc, sync::Arc, borrow::Cow};
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+use nom::{IResult, combinator};
+
+use super::{core::Absorb, error::ParseError, limit::Limit};
+
+impl<'pa, T> Absorb<'pa> for Rc<T>
+where
+    T: Absorb<'pa>,
+{
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(T::absorb::<L>, Rc::new)(input)
+    }
+}
+
+impl<'pa, T> Absorb<'pa> for Arc<T>
+where
+    T: Absorb<'pa>,
+{
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(T::absorb::<L>, Arc::new)(input)
+    }
+}
+
+// the same encoding as `String`
+
+impl<'pa> Absorb<'pa> for Box<str> {
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(<&str>::absorb::<L>, Box::from)(input)
+    }
+}
+
+impl<'pa> Absorb<'pa> for Rc<str> {
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(<&str>::absorb::<L>, Rc::from)(input)
+    }
+}
+
+impl<'pa> Absorb<'pa> for Arc<str> {
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(<&str>::absorb::<L>, Arc::from)(input)
+    }
+}
+
+// the same encoding as `Box<[T]>`
+
+impl<'pa, T> Absorb<'pa> for Rc<[T]>
+where
+    T: Absorb<'pa>,
+{
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(<Box<[T]>>::absorb::<L>, Rc::from)(input)
+    }
+}
+
+impl<'pa, T> Absorb<'pa> for Arc<[T]>
+where
+    T: Absorb<'pa>,
+{
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(<Box<[T]>>::absorb::<L>, Arc::from)(input)
+    }
+}
+
+/// Owned, see `Cow<'pa, str>` and `Cow<'pa, [u8]>` which borrow from the input.
+impl<'pa, T> Absorb<'pa> for Cow<'pa, T>
+where
+    T: Absorb<'pa> + Clone,
+{
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(T::absorb::<L>, Cow::Owned)(input)
+    }
+}
+
+impl<'pa, T> Absorb<'pa> for Cell<T>
+where
+    T: Absorb<'pa>,
+{
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(T::absorb::<L>, Cell::new)(input)
+    }
+}
+
+impl<'pa, T> Absorb<'pa> for RefCell<T>
+where
+    T: Absorb<'pa>,
+{
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(T::absorb::<L>, RefCell::new)(input)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'pa, T> Absorb<'pa> for Mutex<T>
+where
+    T: Absorb<'pa>,
+{
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(T::absorb::<L>, Mutex::new)(input)
+    }
+}
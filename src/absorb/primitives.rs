@@ -1,12 +1,23 @@
 // Copyright 2022 Vladislav Melnik
 // SPDX-License-Identifier: MIT
 
-use core::marker::PhantomData;
+use core::{
+    marker::PhantomData,
+    num::{
+        NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU128, NonZeroUsize, NonZeroI8,
+        NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128,
+    },
+};
 use alloc::boxed::Box;
 
-use nom::{IResult, combinator, number, branch, bytes::complete, sequence};
+use nom::{IResult, combinator, number, bytes::streaming};
 
-use super::{core::Absorb, error::ParseError, limit::Limit};
+use super::{
+    core::Absorb,
+    error::{ParseError, ParseErrorKind},
+    limit::{Limit, LittleEndian, BigEndian},
+    Le, Be,
+};
 
 impl<'pa, T> Absorb<'pa> for PhantomData<T> {
     fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
@@ -31,10 +42,12 @@ impl<'pa> Absorb<'pa> for bool {
     where
         L: Limit,
     {
-        branch::alt((
-            combinator::map(complete::tag(&[0x00][..]), |_| false),
-            combinator::map(complete::tag(&[0xff][..]), |_| true),
-        ))(input)
+        let (rest, v) = number::streaming::u8(input)?;
+        match v {
+            0x00 => Ok((rest, false)),
+            0xff => Ok((rest, true)),
+            v => Err(ParseErrorKind::InvalidBool(v).error(input)),
+        }
     }
 }
 
@@ -43,7 +56,7 @@ impl<'pa> Absorb<'pa> for i8 {
     where
         L: Limit,
     {
-        number::complete::i8(input)
+        number::streaming::i8(input)
     }
 }
 
@@ -52,7 +65,18 @@ impl<'pa> Absorb<'pa> for u8 {
     where
         L: Limit,
     {
-        number::complete::u8(input)
+        number::streaming::u8(input)
+    }
+
+    fn absorb_array<L, const S: usize>(
+        input: &'pa [u8],
+    ) -> IResult<&'pa [u8], [Self; S], ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(streaming::take(S), |input| {
+            <[u8; S]>::try_from(input).expect("impossible to fail here")
+        })(input)
     }
 }
 
@@ -61,7 +85,7 @@ impl<'pa> Absorb<'pa> for i16 {
     where
         L: Limit,
     {
-        number::complete::be_i16(input)
+        number::streaming::i16(L::ENDIANNESS)(input)
     }
 }
 
@@ -70,7 +94,7 @@ impl<'pa> Absorb<'pa> for u16 {
     where
         L: Limit,
     {
-        number::complete::be_u16(input)
+        number::streaming::u16(L::ENDIANNESS)(input)
     }
 }
 
@@ -79,7 +103,7 @@ impl<'pa> Absorb<'pa> for i32 {
     where
         L: Limit,
     {
-        number::complete::be_i32(input)
+        number::streaming::i32(L::ENDIANNESS)(input)
     }
 }
 
@@ -88,7 +112,7 @@ impl<'pa> Absorb<'pa> for u32 {
     where
         L: Limit,
     {
-        number::complete::be_u32(input)
+        number::streaming::u32(L::ENDIANNESS)(input)
     }
 }
 
@@ -97,7 +121,7 @@ impl<'pa> Absorb<'pa> for i64 {
     where
         L: Limit,
     {
-        number::complete::be_i64(input)
+        number::streaming::i64(L::ENDIANNESS)(input)
     }
 }
 
@@ -106,7 +130,25 @@ impl<'pa> Absorb<'pa> for u64 {
     where
         L: Limit,
     {
-        number::complete::be_u64(input)
+        number::streaming::u64(L::ENDIANNESS)(input)
+    }
+}
+
+impl<'pa> Absorb<'pa> for i128 {
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        number::streaming::i128(L::ENDIANNESS)(input)
+    }
+}
+
+impl<'pa> Absorb<'pa> for u128 {
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        number::streaming::u128(L::ENDIANNESS)(input)
     }
 }
 
@@ -115,7 +157,11 @@ impl<'pa> Absorb<'pa> for f32 {
     where
         L: Limit,
     {
-        number::complete::be_f32(input)
+        let (rest, v) = number::streaming::f32(L::ENDIANNESS)(input)?;
+        if L::CANONICAL && v.is_nan() && v.to_bits() != f32::NAN.to_bits() {
+            return Err(ParseErrorKind::NotCanonical("NaN payload").error(input));
+        }
+        Ok((rest, v))
     }
 }
 
@@ -124,31 +170,141 @@ impl<'pa> Absorb<'pa> for f64 {
     where
         L: Limit,
     {
-        number::complete::be_f64(input)
+        let (rest, v) = number::streaming::f64(L::ENDIANNESS)(input)?;
+        if L::CANONICAL && v.is_nan() && v.to_bits() != f64::NAN.to_bits() {
+            return Err(ParseErrorKind::NotCanonical("NaN payload").error(input));
+        }
+        Ok((rest, v))
     }
 }
 
-impl<'pa, A, B> Absorb<'pa> for (A, B)
-where
-    A: Absorb<'pa>,
-    B: Absorb<'pa>,
-{
+impl<'pa> Absorb<'pa> for char {
     fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
     where
         L: Limit,
     {
-        sequence::pair(A::absorb::<L>, B::absorb::<L>)(input)
+        let (rest, v) = number::streaming::u32(L::ENDIANNESS)(input)?;
+        let c = char::from_u32(v).ok_or_else(|| ParseErrorKind::InvalidChar(v).error(input))?;
+        Ok((rest, c))
     }
 }
 
-impl<'pa, const S: usize> Absorb<'pa> for [u8; S] {
+macro_rules! impl_non_zero {
+    ($($t:ty),+) => {
+        $(
+            impl<'pa> Absorb<'pa> for $t {
+                fn absorb<L>(
+                    input: &'pa [u8],
+                ) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+                where
+                    L: Limit,
+                {
+                    let (rest, v) = Absorb::absorb::<L>(input)?;
+                    let v = <$t>::new(v)
+                        .ok_or_else(|| ParseErrorKind::Zero(stringify!($t)).error(input))?;
+                    Ok((rest, v))
+                }
+            }
+        )+
+    };
+}
+
+impl_non_zero!(
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128,
+    NonZeroUsize,
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128
+);
+
+macro_rules! absorb_tuple {
+    ($input:ident, $l:ty;) => {};
+    ($input:ident, $l:ty; $t:ident $v:ident $(, $ts:ident $vs:ident)*) => {
+        let ($input, $v) = $t::absorb::<$l>($input)?;
+        absorb_tuple!($input, <$l as Limit>::Next; $($ts $vs),*);
+    };
+}
+
+// each next element is absorbed with the next limit, like fields of a structure
+macro_rules! impl_tuple {
+    ($($t:ident $v:ident),+) => {
+        impl<'pa, $($t),+> Absorb<'pa> for ($($t,)+)
+        where
+            $($t: Absorb<'pa>,)+
+        {
+            fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+            where
+                L: Limit,
+            {
+                absorb_tuple!(input, L; $($t $v),+);
+                Ok((input, ($($v,)+)))
+            }
+        }
+    };
+}
+
+impl_tuple!(A a);
+impl_tuple!(A a, B b);
+impl_tuple!(A a, B b, C c);
+impl_tuple!(A a, B b, C c, D d);
+impl_tuple!(A a, B b, C c, D d, E e);
+impl_tuple!(A a, B b, C c, D d, E e, F f);
+impl_tuple!(A a, B b, C c, D d, E e, F f, G g);
+impl_tuple!(A a, B b, C c, D d, E e, F f, G g, H h);
+impl_tuple!(A a, B b, C c, D d, E e, F f, G g, H h, I i);
+impl_tuple!(A a, B b, C c, D d, E e, F f, G g, H h, I i, J j);
+impl_tuple!(A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k);
+impl_tuple!(A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k, M m);
+
+/// The default `Absorb::absorb_array`, builds the array in place,
+/// the elements after the error are not absorbed.
+pub(super) fn absorb_array<'pa, T, L, const S: usize>(
+    mut input: &'pa [u8],
+) -> IResult<&'pa [u8], [T; S], ParseError<&'pa [u8]>>
+where
+    T: Absorb<'pa>,
+    L: Limit,
+{
+    let mut error = None;
+    let items = [(); S].map(|()| {
+        if error.is_some() {
+            return None;
+        }
+        match T::absorb::<L>(input) {
+            Ok((rest, item)) => {
+                input = rest;
+                Some(item)
+            }
+            Err(err) => {
+                error = Some(err);
+                None
+            }
+        }
+    });
+    match error {
+        Some(err) => Err(err),
+        None => Ok((
+            input,
+            items.map(|item| item.expect("every item is absorbed")),
+        )),
+    }
+}
+
+impl<'pa, T, const S: usize> Absorb<'pa> for [T; S]
+where
+    T: Absorb<'pa>,
+{
     fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
     where
         L: Limit,
     {
-        combinator::map(complete::take(S), |input| {
-            <[u8; S]>::try_from(input).expect("impossible to fail here")
-        })(input)
+        T::absorb_array::<L, S>(input)
     }
 }
 
@@ -157,7 +313,7 @@ impl<'pa, const S: usize> Absorb<'pa> for &'pa [u8; S] {
     where
         L: Limit,
     {
-        combinator::map(complete::take(S), |input| {
+        combinator::map(streaming::take(S), |input| {
             <&'pa [u8; S]>::try_from(input).expect("impossible to fail here")
         })(input)
     }
@@ -165,22 +321,24 @@ impl<'pa, const S: usize> Absorb<'pa> for &'pa [u8; S] {
 
 impl<'pa, T> Absorb<'pa> for Option<T>
 where
-    T: Absorb<'pa> + Clone,
+    T: Absorb<'pa>,
 {
     fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
     where
         L: Limit,
     {
-        branch::alt((
-            sequence::preceded(
-                complete::tag(0x00_u8.to_be_bytes()),
-                combinator::success(None),
-            ),
-            sequence::preceded(
-                complete::tag(0xff_u8.to_be_bytes()),
-                combinator::map(T::absorb::<L>, Some),
-            ),
-        ))(input)
+        let (rest, v) = number::streaming::u8(input)?;
+        match v {
+            0x00 => Ok((rest, None)),
+            0xff => combinator::map(T::absorb::<L>, Some)(rest),
+            value => {
+                let kind = ParseErrorKind::InvalidDiscriminant {
+                    value,
+                    hint: "Option",
+                };
+                Err(kind.error(input))
+            }
+        }
     }
 }
 
@@ -193,16 +351,18 @@ where
     where
         L: Limit,
     {
-        branch::alt((
-            sequence::preceded(
-                complete::tag(0xff_u8.to_be_bytes()),
-                combinator::map(T::absorb::<L>, Ok),
-            ),
-            sequence::preceded(
-                complete::tag(0xfe_u8.to_be_bytes()),
-                combinator::map(E::absorb::<L>, Err),
-            ),
-        ))(input)
+        let (rest, v) = number::streaming::u8(input)?;
+        match v {
+            0xff => combinator::map(T::absorb::<L>, Ok)(rest),
+            0xfe => combinator::map(E::absorb::<L>, Err)(rest),
+            value => {
+                let kind = ParseErrorKind::InvalidDiscriminant {
+                    value,
+                    hint: "Result",
+                };
+                Err(kind.error(input))
+            }
+        }
     }
 }
 
@@ -217,3 +377,27 @@ where
         combinator::map(T::absorb::<L>, Box::new)(input)
     }
 }
+
+impl<'pa, T> Absorb<'pa> for Le<T>
+where
+    T: Absorb<'pa>,
+{
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(T::absorb::<LittleEndian<L>>, Le)(input)
+    }
+}
+
+impl<'pa, T> Absorb<'pa> for Be<T>
+where
+    T: Absorb<'pa>,
+{
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(T::absorb::<BigEndian<L>>, Be)(input)
+    }
+}
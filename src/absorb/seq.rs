@@ -6,15 +6,17 @@ use alloc::{
     string::{String, ToString},
     boxed::Box,
     vec::Vec,
+    borrow::Cow,
 };
 
-use nom::{IResult, combinator, number, multi};
+use nom::{IResult, combinator, number, multi, error::ErrorKind};
 
 use super::{
-    core::Absorb,
-    error::{ParseError, ParseErrorKind},
-    limit::Limit,
-    DynSized, Collection,
+    core::{Absorb, complete},
+    error::{ParseError, ParseErrorKind, PathSegment},
+    limit::{Limit, LengthPrefix, AsLengthPrefix, WithPrefix},
+    varint::absorb_var_u64,
+    DynSized, Collection, Unordered, Prefixed,
 };
 
 impl<'pa> Absorb<'pa> for usize {
@@ -22,8 +24,21 @@ impl<'pa> Absorb<'pa> for usize {
     where
         L: Limit,
     {
-        let (input, size) = number::complete::be_u32(input)?;
-        L::check(size as usize)
+        let original_input = <&[u8]>::clone(&input);
+        let (input, size) = match L::PREFIX {
+            LengthPrefix::U8 => combinator::map(number::streaming::u8, u64::from)(input)?,
+            LengthPrefix::U16 => {
+                combinator::map(number::streaming::u16(L::ENDIANNESS), u64::from)(input)?
+            }
+            LengthPrefix::U32 => {
+                combinator::map(number::streaming::u32(L::ENDIANNESS), u64::from)(input)?
+            }
+            LengthPrefix::U64 => number::streaming::u64(L::ENDIANNESS)(input)?,
+            LengthPrefix::Var => absorb_var_u64(input)?,
+        };
+        let size = usize::try_from(size)
+            .map_err(|_| ParseErrorKind::Nom(ErrorKind::TooLarge).error(original_input))?;
+        L::check(size)
             .map(|size| (input, size))
             .map_err(|e| ParseErrorKind::Limit(e, L::DESCRIPTION).error(input))
     }
@@ -34,10 +49,47 @@ impl<'pa> Absorb<'pa> for &'pa str {
     where
         L: Limit,
     {
-        combinator::map_res(multi::length_data(usize::absorb::<L>), str::from_utf8)(input)
+        let (input, bytes) = multi::length_data(usize::absorb::<L>)(input)?;
+        let s = str::from_utf8(bytes).map_err(|err| {
+            let valid_up_to = err.valid_up_to();
+            ParseErrorKind::InvalidUtf8 { valid_up_to }.error(bytes)
+        })?;
+        Ok((input, s))
+    }
+}
+
+/// The `as_str` field, the string parsed with `FromStr`, the error of `FromStr`
+/// is kept by `ParseError::custom`.
+#[cfg(any(feature = "std", feature = "codec"))]
+pub fn absorb_parsed<T, L>(input: &[u8]) -> IResult<&[u8], T, ParseError<&[u8]>>
+where
+    T: str::FromStr,
+    T::Err: Into<Box<dyn std::error::Error + Send + Sync>>,
+    L: Limit,
+{
+    let (rest, s) = <&str>::absorb::<L>(input)?;
+    match s.parse() {
+        Ok(value) => Ok((rest, value)),
+        Err(err) => Err(nom::Err::Error(ParseError::custom(
+            input,
+            ErrorKind::MapRes,
+            err,
+        ))),
     }
 }
 
+/// The `as_str` field, the string parsed with `FromStr`, the message of
+/// the error of `FromStr` is kept.
+#[cfg(not(any(feature = "std", feature = "codec")))]
+pub fn absorb_parsed<T, L>(input: &[u8]) -> IResult<&[u8], T, ParseError<&[u8]>>
+where
+    T: str::FromStr,
+    T::Err: core::fmt::Display,
+    L: Limit,
+{
+    combinator::map_res(<&str>::absorb::<L>, str::parse)(input)
+}
+
 impl<'pa> Absorb<'pa> for String {
     fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
     where
@@ -47,6 +99,24 @@ impl<'pa> Absorb<'pa> for String {
     }
 }
 
+impl<'pa> Absorb<'pa> for Cow<'pa, str> {
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(<&str>::absorb::<L>, Cow::Borrowed)(input)
+    }
+}
+
+impl<'pa> Absorb<'pa> for Cow<'pa, [u8]> {
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(<&[u8]>::absorb::<L>, Cow::Borrowed)(input)
+    }
+}
+
 impl<'pa, T> Absorb<'pa> for DynSized<T>
 where
     T: Absorb<'pa>,
@@ -57,7 +127,7 @@ where
     {
         combinator::map_parser(
             multi::length_data(usize::absorb::<L>),
-            combinator::all_consuming(combinator::map(T::absorb::<L::Inner>, DynSized)),
+            combinator::all_consuming(complete(combinator::map(T::absorb::<L::Inner>, DynSized))),
         )(input)
     }
 }
@@ -69,14 +139,28 @@ where
     C: Default + Extend<T>,
     T: Absorb<'pa>,
 {
-    use nom::{
-        InputLength,
-        error::{ErrorKind, ParseError as _},
-        Err,
-    };
+    fold_parser_with::<C, T, L, _>(|acc, value, _| {
+        acc.extend(Some(value));
+        Ok(())
+    })
+}
+
+/// Parse the elements until the end of the input, `insert` takes the element
+/// and its encoding, and may reject the element, the error points at the element.
+pub(super) fn fold_parser_with<'pa, C, T, L, F>(
+    mut insert: F,
+) -> impl FnMut(&'pa [u8]) -> IResult<&'pa [u8], C, ParseError<&'pa [u8]>>
+where
+    L: Limit,
+    C: Default,
+    T: Absorb<'pa>,
+    F: FnMut(&mut C, T, &'pa [u8]) -> Result<(), ParseErrorKind>,
+{
+    use nom::InputLength;
 
     move |mut input| {
         let mut acc = C::default();
+        let mut index = 0;
         L::check(input.input_len())
             .map_err(|err| ParseErrorKind::Limit(err, L::DESCRIPTION).error(input))?;
         while !input.is_empty() {
@@ -84,21 +168,16 @@ where
             match T::absorb::<L::Inner>(<&[u8]>::clone(&input)) {
                 Ok((tail, value)) => {
                     if tail.input_len() == len {
-                        let msg = "zero sized infinite loop";
-                        let kind = ParseErrorKind::Custom(ErrorKind::ManyMN, msg.to_string());
-                        return Err(kind.error(tail));
+                        return Err(ParseErrorKind::ZeroSizedLoop.error(tail));
                     }
-                    acc.extend(Some(value));
+                    let encoded = &input[..(len - tail.input_len())];
+                    insert(&mut acc, value, encoded).map_err(|kind| {
+                        kind.error(input).map(|e| e.push(PathSegment::Index(index)))
+                    })?;
                     input = tail;
+                    index += 1;
                 }
-                Err(Err::Error(err)) => {
-                    return Err(Err::Error(ParseError::append(
-                        input,
-                        ErrorKind::ManyMN,
-                        err,
-                    )));
-                }
-                Err(e) => return Err(e),
+                Err(err) => return Err(err.map(|e| e.push(PathSegment::Index(index)))),
             }
         }
 
@@ -106,6 +185,25 @@ where
     }
 }
 
+/// In canonical mode the elements are sorted by their encoding, without duplicates.
+pub(super) fn canonical_order<'pa, L>(
+    last: &mut Option<&'pa [u8]>,
+    encoded: &'pa [u8],
+) -> Result<(), ParseErrorKind>
+where
+    L: Limit,
+{
+    if L::CANONICAL {
+        match *last {
+            Some(last) if last == encoded => return Err(ParseErrorKind::DuplicateKey),
+            Some(last) if last > encoded => return Err(ParseErrorKind::UnsortedKey),
+            _ => (),
+        }
+        *last = Some(encoded);
+    }
+    Ok(())
+}
+
 impl<'pa, T> Absorb<'pa> for Box<[T]>
 where
     T: Absorb<'pa>,
@@ -149,7 +247,7 @@ where
     {
         combinator::map_parser(
             multi::length_data(usize::absorb::<L>),
-            combinator::all_consuming(fold_parser::<Vec<T>, T, L>()),
+            combinator::all_consuming(complete(fold_parser::<Vec<T>, T, L>())),
         )(input)
     }
 
@@ -160,7 +258,7 @@ where
     {
         combinator::map_parser(
             multi::length_data(usize::absorb::<L>),
-            combinator::all_consuming(fold_parser::<Vec<T>, T, L>()),
+            combinator::all_consuming(complete(fold_parser::<Vec<T>, T, L>())),
         )(input)
     }
 }
@@ -187,3 +285,35 @@ where
         combinator::map(fold_parser::<C, C::Item, L>(), Collection)(input)
     }
 }
+
+impl<'pa, C> Absorb<'pa> for Unordered<C>
+where
+    C: Default + IntoIterator + Extend<C::Item>,
+    C::Item: Absorb<'pa>,
+{
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        let mut last = None;
+        let insert = move |acc: &mut C, value, encoded| {
+            canonical_order::<L>(&mut last, encoded)?;
+            acc.extend(Some(value));
+            Ok(())
+        };
+        combinator::map(fold_parser_with::<C, C::Item, L, _>(insert), Unordered)(input)
+    }
+}
+
+impl<'pa, P, T> Absorb<'pa> for Prefixed<P, T>
+where
+    P: AsLengthPrefix,
+    T: Absorb<'pa>,
+{
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(T::absorb::<WithPrefix<P, L>>, Prefixed::from)(input)
+    }
+}
@@ -1,14 +1,41 @@
-use std::net::SocketAddr;
+use std::{
+    str,
+    ffi::OsString,
+    path::{Path, PathBuf},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    time::{Duration, SystemTime},
+};
 
-use nom::IResult;
+use nom::{IResult, combinator};
 
 use super::{
     core::Absorb,
     error::{ParseError, ParseErrorKind},
     limit::Limit,
+    super::{WithScope, UnixTimestamp, Precision, NANOS_PER_SECOND},
 };
 
-impl<'pa> Absorb<'pa> for SocketAddr {
+// the addresses and ports are in network byte order regardless of `L`
+
+impl<'pa> Absorb<'pa> for Ipv4Addr {
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(<[u8; 4]>::absorb::<()>, Ipv4Addr::from)(input)
+    }
+}
+
+impl<'pa> Absorb<'pa> for Ipv6Addr {
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(<[u8; 16]>::absorb::<()>, Ipv6Addr::from)(input)
+    }
+}
+
+impl<'pa> Absorb<'pa> for IpAddr {
     fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
     where
         L: Limit,
@@ -16,19 +43,196 @@ impl<'pa> Absorb<'pa> for SocketAddr {
         let original_input = <&[u8]>::clone(&input);
         let (input, tag) = u8::absorb::<()>(input)?;
         match tag {
-            4 => nom::combinator::map(<([u8; 4], u16)>::absorb::<()>, |(ip, port)| {
-                SocketAddr::new(ip.into(), port)
-            })(input),
-            6 => nom::combinator::map(<([u8; 16], u16)>::absorb::<()>, |(ip, port)| {
-                SocketAddr::new(ip.into(), port)
-            })(input),
+            4 => combinator::map(Ipv4Addr::absorb::<()>, IpAddr::V4)(input),
+            6 => combinator::map(Ipv6Addr::absorb::<()>, IpAddr::V6)(input),
             tag => {
-                let kind = ParseErrorKind::UnknownTag {
-                    tag: tag.to_string(),
-                    hint: "",
-                };
+                let kind = ParseErrorKind::unknown_tag(tag, "IpAddr");
                 Err(kind.error(original_input))
             }
         }
     }
 }
+
+impl<'pa> Absorb<'pa> for SocketAddrV4 {
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(<(Ipv4Addr, u16)>::absorb::<()>, |(ip, port)| {
+            SocketAddrV4::new(ip, port)
+        })(input)
+    }
+}
+
+impl<'pa> Absorb<'pa> for SocketAddrV6 {
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(<(Ipv6Addr, u16)>::absorb::<()>, |(ip, port)| {
+            SocketAddrV6::new(ip, port, 0, 0)
+        })(input)
+    }
+}
+
+impl<'pa> Absorb<'pa> for WithScope<SocketAddrV6> {
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(
+            <(Ipv6Addr, u16, u32, u32)>::absorb::<()>,
+            |(ip, port, flowinfo, scope_id)| {
+                WithScope(SocketAddrV6::new(ip, port, flowinfo, scope_id))
+            },
+        )(input)
+    }
+}
+
+fn absorb_socket_addr<'pa, V6>(
+    input: &'pa [u8],
+    v6: fn(V6) -> SocketAddrV6,
+) -> IResult<&'pa [u8], SocketAddr, ParseError<&'pa [u8]>>
+where
+    V6: Absorb<'pa>,
+{
+    let original_input = <&[u8]>::clone(&input);
+    let (input, tag) = u8::absorb::<()>(input)?;
+    match tag {
+        4 => combinator::map(SocketAddrV4::absorb::<()>, SocketAddr::V4)(input),
+        6 => combinator::map(V6::absorb::<()>, |addr| SocketAddr::V6(v6(addr)))(input),
+        tag => {
+            let kind = ParseErrorKind::unknown_tag(tag, "SocketAddr");
+            Err(kind.error(original_input))
+        }
+    }
+}
+
+impl<'pa> Absorb<'pa> for SocketAddr {
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        absorb_socket_addr(input, |addr: SocketAddrV6| addr)
+    }
+}
+
+impl<'pa> Absorb<'pa> for WithScope<SocketAddr> {
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        let (input, addr) = absorb_socket_addr(input, |WithScope(addr)| addr)?;
+        Ok((input, WithScope(addr)))
+    }
+}
+
+fn absorb_nanos<L>(input: &[u8]) -> IResult<&[u8], u32, ParseError<&[u8]>>
+where
+    L: Limit,
+{
+    let (rest, nanos) = u32::absorb::<L>(input)?;
+    if nanos >= NANOS_PER_SECOND {
+        return Err(ParseErrorKind::InvalidNanos(nanos).error(input));
+    }
+    Ok((rest, nanos))
+}
+
+/// The seconds and the nanoseconds.
+impl<'pa> Absorb<'pa> for Duration {
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        let (input, secs) = u64::absorb::<L>(input)?;
+        let (input, nanos) = absorb_nanos::<L>(input)?;
+        Ok((input, Duration::new(secs, nanos)))
+    }
+}
+
+/// The time `secs` seconds since the Unix epoch, negative before it,
+/// plus `nanos`, the inverse of `unix_secs_nanos` of emit.
+fn unix_time(secs: i64, nanos: u32) -> Option<SystemTime> {
+    let time = if secs >= 0 {
+        SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(secs as u64))
+    } else {
+        SystemTime::UNIX_EPOCH.checked_sub(Duration::from_secs(secs.unsigned_abs()))
+    };
+    time?.checked_add(Duration::from_nanos(nanos.into()))
+}
+
+/// The seconds since the Unix epoch as `i64` and the nanoseconds.
+impl<'pa> Absorb<'pa> for SystemTime {
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        let original_input = <&[u8]>::clone(&input);
+        let (input, secs) = i64::absorb::<L>(input)?;
+        let (input, nanos) = absorb_nanos::<L>(input)?;
+        let time = unix_time(secs, nanos)
+            .ok_or_else(|| ParseErrorKind::TimeOverflow.error(original_input))?;
+        Ok((input, time))
+    }
+}
+
+impl<'pa, P> Absorb<'pa> for UnixTimestamp<P>
+where
+    P: Precision,
+{
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        let (rest, units) = i64::absorb::<L>(input)?;
+        let per_second = P::PER_SECOND as i64;
+        let nanos = units.rem_euclid(per_second) as u32 * (NANOS_PER_SECOND / P::PER_SECOND as u32);
+        let time = unix_time(units.div_euclid(per_second), nanos)
+            .ok_or_else(|| ParseErrorKind::TimeOverflow.error(input))?;
+        Ok((rest, time.into()))
+    }
+}
+
+// the same encoding as `String`, on Unix the raw bytes of `OsStr`,
+// which are not necessarily UTF-8, unless `L::STRICT`
+
+impl<'pa> Absorb<'pa> for OsString {
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        let (input, bytes) = <&[u8]>::absorb::<L>(input)?;
+        let s = match str::from_utf8(bytes) {
+            Ok(s) => OsString::from(s),
+            #[cfg(unix)]
+            Err(_) if !L::STRICT => {
+                use std::os::unix::ffi::OsStringExt;
+
+                OsString::from_vec(bytes.to_vec())
+            }
+            Err(err) => {
+                let valid_up_to = err.valid_up_to();
+                return Err(ParseErrorKind::NonUtf8Path { valid_up_to }.error(bytes));
+            }
+        };
+        Ok((input, s))
+    }
+}
+
+impl<'pa> Absorb<'pa> for PathBuf {
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(OsString::absorb::<L>, PathBuf::from)(input)
+    }
+}
+
+impl<'pa> Absorb<'pa> for Box<Path> {
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(PathBuf::absorb::<L>, PathBuf::into_boxed_path)(input)
+    }
+}
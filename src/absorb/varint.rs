@@ -0,0 +1,54 @@
+// Copyright 2022 Vladislav Melnik
+// SPDX-License-Identifier: MIT
+
+use nom::{IResult, Err, Needed, combinator, error::ErrorKind};
+
+use super::{
+    core::Absorb,
+    error::{ParseError, ParseErrorKind},
+    limit::Limit,
+    VarU64, VarI64,
+};
+
+/// Parse unsigned LEB128, rejects the value which does not fit in `u64`
+/// and the encoding which has redundant trailing zero groups.
+pub(super) fn absorb_var_u64(input: &[u8]) -> IResult<&[u8], u64, ParseError<&[u8]>> {
+    let mut value = 0;
+    for (i, byte) in input.iter().enumerate() {
+        // the tenth byte holds the last bit
+        if i == 9 && *byte > 1 {
+            return Err(ParseErrorKind::Nom(ErrorKind::TooLarge).error(input));
+        }
+        let group = u64::from(byte & 0x7f);
+        value |= group << (7 * i);
+        if byte & 0x80 == 0 {
+            if i != 0 && group == 0 {
+                return Err(ParseErrorKind::Overlong.error(input));
+            }
+            return Ok((&input[(i + 1)..], value));
+        }
+    }
+    Err(Err::Incomplete(Needed::new(1)))
+}
+
+const fn unzigzag(v: u64) -> i64 {
+    (v >> 1) as i64 ^ -((v & 1) as i64)
+}
+
+impl<'pa> Absorb<'pa> for VarU64 {
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(absorb_var_u64, VarU64)(input)
+    }
+}
+
+impl<'pa> Absorb<'pa> for VarI64 {
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        combinator::map(absorb_var_u64, |v| VarI64(unzigzag(v)))(input)
+    }
+}
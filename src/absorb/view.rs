@@ -0,0 +1,197 @@
+// Copyright 2022 Vladislav Melnik
+// SPDX-License-Identifier: MIT
+
+use core::fmt;
+
+use nom::{IResult, multi};
+
+use super::{
+    core::{Absorb, complete},
+    error::{ParseError, ParseErrorKind, PathSegment},
+    limit::Limit,
+};
+
+type Element<'pa, T> = fn(&'pa [u8]) -> IResult<&'pa [u8], T, ParseError<&'pa [u8]>>;
+
+/// Borrowed `Vec<T>`, the same encoding, the length in bytes and the elements.
+/// Absorb checks the length and the limit, the elements are absorbed
+/// on demand by the iterator, with the limit `L::Inner`.
+pub struct SeqView<'pa, T> {
+    bytes: &'pa [u8],
+    element: Element<'pa, T>,
+}
+
+/// Borrowed `Box<[T]>`, the same encoding, the elements until the end
+/// of the input, see `SeqView`.
+pub struct RestView<'pa, T> {
+    bytes: &'pa [u8],
+    element: Element<'pa, T>,
+}
+
+/// The iterator over the elements of `SeqView` or `RestView`. Yields the error
+/// if the element is malformed and stops after it, the error points inside
+/// of the original input.
+pub struct ViewIter<'pa, T> {
+    input: &'pa [u8],
+    element: Element<'pa, T>,
+    index: usize,
+}
+
+impl<'pa, T> Clone for SeqView<'pa, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'pa, T> Copy for SeqView<'pa, T> {}
+
+impl<'pa, T> Clone for RestView<'pa, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'pa, T> Copy for RestView<'pa, T> {}
+
+impl<'pa, T> fmt::Debug for SeqView<'pa, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SeqView")
+            .field(&hex::encode(self.bytes))
+            .finish()
+    }
+}
+
+impl<'pa, T> fmt::Debug for RestView<'pa, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RestView")
+            .field(&hex::encode(self.bytes))
+            .finish()
+    }
+}
+
+impl<'pa, T> SeqView<'pa, T> {
+    /// The encoded elements, without the length.
+    pub fn as_bytes(&self) -> &'pa [u8] {
+        self.bytes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn iter(&self) -> ViewIter<'pa, T> {
+        ViewIter {
+            input: self.bytes,
+            element: self.element,
+            index: 0,
+        }
+    }
+}
+
+impl<'pa, T> RestView<'pa, T> {
+    /// The encoded elements.
+    pub fn as_bytes(&self) -> &'pa [u8] {
+        self.bytes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn iter(&self) -> ViewIter<'pa, T> {
+        ViewIter {
+            input: self.bytes,
+            element: self.element,
+            index: 0,
+        }
+    }
+}
+
+impl<'pa, T> IntoIterator for SeqView<'pa, T> {
+    type Item = Result<T, ParseError<&'pa [u8]>>;
+    type IntoIter = ViewIter<'pa, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'pa, T> IntoIterator for RestView<'pa, T> {
+    type Item = Result<T, ParseError<&'pa [u8]>>;
+    type IntoIter = ViewIter<'pa, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'pa, T> Iterator for ViewIter<'pa, T> {
+    type Item = Result<T, ParseError<&'pa [u8]>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.input.is_empty() {
+            return None;
+        }
+        let err = match complete(self.element)(self.input) {
+            Ok((tail, value)) if tail.len() < self.input.len() => {
+                self.input = tail;
+                self.index += 1;
+                return Some(Ok(value));
+            }
+            Ok((tail, _)) => ParseErrorKind::ZeroSizedLoop.error(tail),
+            Err(err) => err,
+        };
+        // the rest is unreadable
+        self.input = &self.input[self.input.len()..];
+        match err {
+            nom::Err::Error(err) | nom::Err::Failure(err) => {
+                Some(Err(err.push(PathSegment::Index(self.index))))
+            }
+            nom::Err::Incomplete(_) => unreachable!("`complete` turns incomplete into error"),
+        }
+    }
+}
+
+impl<'pa, T> Absorb<'pa> for SeqView<'pa, T>
+where
+    T: Absorb<'pa>,
+{
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        let (input, bytes) = multi::length_data(usize::absorb::<L>)(input)?;
+        let element = T::absorb::<L::Inner>;
+        Ok((input, SeqView { bytes, element }))
+    }
+}
+
+impl<'pa, T> Absorb<'pa> for RestView<'pa, T>
+where
+    T: Absorb<'pa>,
+{
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        L::check(input.len())
+            .map_err(|err| ParseErrorKind::Limit(err, L::DESCRIPTION).error(input))?;
+        let element = T::absorb::<L::Inner>;
+        Ok((
+            &input[input.len()..],
+            RestView {
+                bytes: input,
+                element,
+            },
+        ))
+    }
+}
+
+impl<'pa> Absorb<'pa> for &'pa [u8] {
+    fn absorb<L>(input: &'pa [u8]) -> IResult<&'pa [u8], Self, ParseError<&'pa [u8]>>
+    where
+        L: Limit,
+    {
+        multi::length_data(usize::absorb::<L>)(input)
+    }
+}
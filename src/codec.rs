@@ -4,7 +4,7 @@ use std::io;
 use tokio_util::codec::{Encoder, Decoder};
 use bytes::BytesMut;
 
-use super::{Absorb, ParseError, Emit, RadiationBuffer};
+use super::{Absorb, AbsorbExt, ParseError, Emit, RadiationBuffer};
 
 impl RadiationBuffer for BytesMut {
     fn pos(&self) -> usize {
@@ -34,16 +34,18 @@ where
     type Item = T;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        let pos = src.as_ptr() as usize;
-        match T::absorb::<()>(&*src).map_err(|err| err.map(ParseError::into_vec)) {
-            Ok((remaining, v)) => {
-                let len = remaining.as_ptr() as usize - pos;
+        match T::absorb_streaming(&*src).map_err(|err| err.map(ParseError::into_vec)) {
+            Ok((v, len)) => {
                 let _ = src.split_to(len);
                 Ok(Some(v))
             }
-            Err(nom::Err::Incomplete(_)) => Ok(None),
-            Err(nom::Err::Error(err)) if err.kind.is_eof() => Ok(None),
-            Err(err) => Err(io::Error::new(io::ErrorKind::Other, err)),
+            Err(nom::Err::Incomplete(needed)) => {
+                if let nom::Needed::Size(size) = needed {
+                    src.reserve(size.get());
+                }
+                Ok(None)
+            }
+            Err(nom::Err::Error(err) | nom::Err::Failure(err)) => Err(err.into()),
         }
     }
 }
@@ -55,8 +57,11 @@ where
     type Error = io::Error;
 
     fn encode(&mut self, item: T, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        item.emit(dst);
-
-        Ok(())
+        // the partially emitted item must not be sent
+        let len = dst.len();
+        item.emit::<()>(dst).map_err(|err| {
+            dst.truncate(len);
+            io::Error::from(err)
+        })
     }
 }
@@ -1,7 +1,7 @@
 use pqcrypto_traits::sign::{SecretKey, PublicKey};
 use pqcrypto_dilithium::{dilithium2, dilithium3, dilithium5};
 
-use crate::{Absorb, Emit, Limit, ParseError};
+use crate::{Absorb, Emit, EmitError, EncodedLen, Limit, ParseError};
 
 macro_rules! impl_pk {
     ($t:ty, $f:expr) => {
@@ -14,12 +14,27 @@ macro_rules! impl_pk {
             }
         }
 
+        impl EncodedLen for $t {
+            const SIZE: Option<usize> = Some($f);
+
+            fn encoded_len_with<L>(&self) -> usize
+            where
+                L: Limit,
+            {
+                $f
+            }
+        }
+
         impl<W> Emit<W> for $t
         where
             W: for<'a> Extend<&'a u8>,
         {
-            fn emit(&self, buffer: &mut W) {
+            fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+            where
+                L: Limit,
+            {
                 buffer.extend(self.as_bytes());
+                Ok(())
             }
         }
     };
@@ -1,30 +1,86 @@
-use core::sync::atomic::{AtomicUsize, Ordering, AtomicU64, AtomicI64};
+use core::sync::atomic::{
+    Ordering, AtomicBool, AtomicU8, AtomicU16, AtomicU32, AtomicU64, AtomicUsize, AtomicI8,
+    AtomicI16, AtomicI32, AtomicI64, AtomicIsize,
+};
 
-use super::core::Emit;
+use super::{core::Emit, error::EmitError, len::EncodedLen, Limit};
 
-impl<W> Emit<W> for AtomicUsize
+/// The ordering of the load, `L::ORDERING` without its store half,
+/// `load` panics with `Release` and `AcqRel`.
+fn load_ordering<L>() -> Ordering
 where
-    W: for<'a> Extend<&'a u8>,
+    L: Limit,
 {
-    fn emit(&self, buffer: &mut W) {
-        self.load(Ordering::Relaxed).emit(buffer)
+    match L::ORDERING {
+        Ordering::Release => Ordering::Relaxed,
+        Ordering::AcqRel => Ordering::Acquire,
+        ordering => ordering,
     }
 }
 
-impl<W> Emit<W> for AtomicU64
-where
-    W: for<'a> Extend<&'a u8>,
-{
-    fn emit(&self, buffer: &mut W) {
-        self.load(Ordering::Relaxed).emit(buffer)
+// loaded with `L::ORDERING`, see `Acquire` and `SeqCst`
+macro_rules! impl_atomic {
+    ($($t:ty => $u:ty),+) => {
+        $(
+            impl EncodedLen for $t {
+                const SIZE: Option<usize> = <$u as EncodedLen>::SIZE;
+
+                fn encoded_len_with<L>(&self) -> usize
+                where
+                    L: Limit,
+                {
+                    self.load(load_ordering::<L>()).encoded_len_with::<L>()
+                }
+            }
+
+            impl<W> Emit<W> for $t
+            where
+                W: for<'a> Extend<&'a u8>,
+            {
+                fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+                where
+                    L: Limit,
+                {
+                    self.load(load_ordering::<L>()).emit::<L>(buffer)
+                }
+            }
+        )+
+    };
+}
+
+impl_atomic!(
+    AtomicBool => bool,
+    AtomicU8 => u8,
+    AtomicU16 => u16,
+    AtomicU32 => u32,
+    AtomicU64 => u64,
+    AtomicUsize => usize,
+    AtomicI8 => i8,
+    AtomicI16 => i16,
+    AtomicI32 => i32,
+    AtomicI64 => i64
+);
+
+// encoded as `i64`, the size of `isize` depends on the platform
+impl EncodedLen for AtomicIsize {
+    const SIZE: Option<usize> = i64::SIZE;
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        (self.load(load_ordering::<L>()) as i64).encoded_len_with::<L>()
     }
 }
 
-impl<W> Emit<W> for AtomicI64
+impl<W> Emit<W> for AtomicIsize
 where
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
-        self.load(Ordering::Relaxed).emit(buffer)
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        (self.load(load_ordering::<L>()) as i64).emit::<L>(buffer)
     }
 }
@@ -0,0 +1,73 @@
+// Copyright 2022 Vladislav Melnik
+// SPDX-License-Identifier: MIT
+
+use alloc::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    boxed::Box,
+    string::String,
+    vec::Vec,
+};
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+
+use super::{core::Emit, Canonical, VarU64, VarI64};
+use crate::Absorb;
+
+/// Implemented for the owned standard types, and derived for user types
+/// with `#[derive(IsCanonical)]`, including the borrowing ones.
+pub trait IsCanonical {
+    /// The regular encoding of the value is canonical and stable, e.g. floats
+    /// have no NaN payload, hash collections happen to be sorted, and
+    /// the custom emitters produce the bytes they accept.
+    fn is_canonical(&self) -> bool;
+}
+
+/// The check, `reemit` absorbs the canonical bytes in canonical mode,
+/// the whole input, and emits the value again.
+pub fn is_canonical<T, F>(value: &T, reemit: F) -> bool
+where
+    T: Emit<Vec<u8>>,
+    F: FnOnce(&[u8]) -> Option<Vec<u8>>,
+{
+    let (regular, canonical) = match (value.chain(vec![]), value.chain_canonical(vec![])) {
+        (Ok(regular), Ok(canonical)) => (regular, canonical),
+        _ => return false,
+    };
+    regular == canonical && reemit(&canonical).as_ref() == Some(&canonical)
+}
+
+fn reemit<T>(bytes: &[u8]) -> Option<Vec<u8>>
+where
+    T: Emit<Vec<u8>> + for<'pa> Absorb<'pa>,
+{
+    match T::absorb::<Canonical>(bytes) {
+        Ok((&[], v)) => v.chain_canonical(vec![]).ok(),
+        _ => None,
+    }
+}
+
+macro_rules! impl_owned {
+    ($(<$($p:ident),*> $t:ty;)*) => {
+        $(
+            impl<$($p),*> IsCanonical for $t
+            where
+                Self: Emit<Vec<u8>> + for<'pa> Absorb<'pa>,
+            {
+                fn is_canonical(&self) -> bool {
+                    is_canonical(self, reemit::<Self>)
+                }
+            }
+        )*
+    };
+}
+
+impl_owned!(
+    <> bool; <> char; <> f32; <> f64;
+    <> i8; <> u8; <> i16; <> u16; <> i32; <> u32; <> i64; <> u64; <> i128; <> u128;
+    <> VarU64; <> VarI64; <> String;
+    <T> Option<T>; <T> Box<T>; <T> Vec<T>; <T> VecDeque<T>;
+    <T> BTreeSet<T>; <K, V> BTreeMap<K, V>;
+);
+
+#[cfg(feature = "std")]
+impl_owned!(<T, S> HashSet<T, S>; <K, V, S> HashMap<K, V, S>;);
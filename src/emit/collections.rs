@@ -0,0 +1,283 @@
+// Copyright 2022 Vladislav Melnik
+// SPDX-License-Identifier: MIT
+
+use alloc::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    vec::Vec,
+};
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+
+use super::{core::Emit, error::EmitError, len::EncodedLen, Limit};
+
+/// The elements of `Unordered<C>`, sorted by their encoding in canonical mode.
+pub(super) fn emit_unordered<C, L, W>(items: C, buffer: &mut W) -> Result<(), EmitError>
+where
+    C: IntoIterator,
+    C::Item: Emit<W> + Emit<Vec<u8>>,
+    L: Limit,
+    W: for<'a> Extend<&'a u8>,
+{
+    if L::CANONICAL {
+        let emit = |v: C::Item, b: &mut Vec<u8>| Emit::<Vec<u8>>::emit::<L>(&v, b);
+        for encoding in sorted_encodings(items.into_iter(), emit)? {
+            buffer.extend(&encoding);
+        }
+    } else {
+        for v in items {
+            Emit::<W>::emit::<L>(&v, buffer)?;
+        }
+    }
+    Ok(())
+}
+
+/// The canonical order of the unordered collection, the encodings
+/// of the elements sorted, duplicates are not allowed.
+fn sorted_encodings<I, F>(items: I, mut emit: F) -> Result<Vec<Vec<u8>>, EmitError>
+where
+    I: Iterator,
+    F: FnMut(I::Item, &mut Vec<u8>) -> Result<(), EmitError>,
+{
+    let mut encodings = items
+        .map(|item| {
+            let mut buffer = vec![];
+            emit(item, &mut buffer).map(|()| buffer)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    encodings.sort_unstable();
+    if encodings.windows(2).any(|w| w[0] == w[1]) {
+        return Err(EmitError::DuplicateKey);
+    }
+    Ok(encodings)
+}
+
+/// The length of the elements, the elements take `L::Inner`.
+fn items_len<'a, T, I, L>(items: I) -> usize
+where
+    T: EncodedLen + 'a,
+    I: ExactSizeIterator<Item = &'a T>,
+    L: Limit,
+{
+    match T::SIZE {
+        Some(size) => size * items.len(),
+        None => items.map(T::encoded_len_with::<L::Inner>).sum(),
+    }
+}
+
+/// The length of the entries, the key takes `L::Inner`, and the value
+/// takes the next limit, like the tuple `(K, V)`.
+fn entries_len<'a, K, V, I, L>(entries: I) -> usize
+where
+    K: EncodedLen + 'a,
+    V: EncodedLen + 'a,
+    I: ExactSizeIterator<Item = (&'a K, &'a V)>,
+    L: Limit,
+{
+    match (K::SIZE, V::SIZE) {
+        (Some(k), Some(v)) => (k + v) * entries.len(),
+        _ => entries
+            .map(|(k, v)| {
+                k.encoded_len_with::<L::Inner>() + v.encoded_len_with::<<L::Inner as Limit>::Next>()
+            })
+            .sum(),
+    }
+}
+
+fn emit_items<'a, T, I, L, W>(len: usize, items: I, buffer: &mut W) -> Result<(), EmitError>
+where
+    T: Emit<W> + 'a,
+    I: Iterator<Item = &'a T>,
+    L: Limit,
+    W: for<'b> Extend<&'b u8>,
+{
+    len.emit::<L>(buffer)?;
+    for v in items {
+        v.emit::<L::Inner>(buffer)?;
+    }
+    Ok(())
+}
+
+fn emit_entries<'a, K, V, I, L, W>(len: usize, entries: I, buffer: &mut W) -> Result<(), EmitError>
+where
+    K: Emit<W> + 'a,
+    V: Emit<W> + 'a,
+    I: Iterator<Item = (&'a K, &'a V)>,
+    L: Limit,
+    W: for<'b> Extend<&'b u8>,
+{
+    len.emit::<L>(buffer)?;
+    for (k, v) in entries {
+        k.emit::<L::Inner>(buffer)?;
+        v.emit::<<L::Inner as Limit>::Next>(buffer)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+fn emit_sorted<L, W>(len: usize, encodings: Vec<Vec<u8>>, buffer: &mut W) -> Result<(), EmitError>
+where
+    L: Limit,
+    W: for<'b> Extend<&'b u8>,
+{
+    len.emit::<L>(buffer)?;
+    for encoding in encodings {
+        buffer.extend(&encoding);
+    }
+    Ok(())
+}
+
+impl<T> EncodedLen for VecDeque<T>
+where
+    T: EncodedLen,
+{
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        let len = items_len::<_, _, L>(self.iter());
+        len.encoded_len_with::<L>() + len
+    }
+}
+
+impl<T, W> Emit<W> for VecDeque<T>
+where
+    T: Emit<W>,
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        let len = items_len::<_, _, L>(self.iter());
+        emit_items::<_, _, L, _>(len, self.iter(), buffer)
+    }
+}
+
+impl<T> EncodedLen for BTreeSet<T>
+where
+    T: EncodedLen,
+{
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        let len = items_len::<_, _, L>(self.iter());
+        len.encoded_len_with::<L>() + len
+    }
+}
+
+impl<T, W> Emit<W> for BTreeSet<T>
+where
+    T: Emit<W>,
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        let len = items_len::<_, _, L>(self.iter());
+        emit_items::<_, _, L, _>(len, self.iter(), buffer)
+    }
+}
+
+impl<K, V> EncodedLen for BTreeMap<K, V>
+where
+    K: EncodedLen,
+    V: EncodedLen,
+{
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        let len = entries_len::<_, _, _, L>(self.iter());
+        len.encoded_len_with::<L>() + len
+    }
+}
+
+impl<K, V, W> Emit<W> for BTreeMap<K, V>
+where
+    K: Emit<W>,
+    V: Emit<W>,
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        let len = entries_len::<_, _, _, L>(self.iter());
+        emit_entries::<_, _, _, L, _>(len, self.iter(), buffer)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S> EncodedLen for HashSet<T, S>
+where
+    T: EncodedLen,
+{
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        let len = items_len::<_, _, L>(self.iter());
+        len.encoded_len_with::<L>() + len
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S, W> Emit<W> for HashSet<T, S>
+where
+    T: Emit<W> + Emit<Vec<u8>>,
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        let len = items_len::<_, _, L>(self.iter());
+        if L::CANONICAL {
+            let emit = |v: &T, b: &mut Vec<u8>| Emit::<Vec<u8>>::emit::<L::Inner>(v, b);
+            emit_sorted::<L, _>(len, sorted_encodings(self.iter(), emit)?, buffer)
+        } else {
+            emit_items::<_, _, L, _>(len, self.iter(), buffer)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S> EncodedLen for HashMap<K, V, S>
+where
+    K: EncodedLen,
+    V: EncodedLen,
+{
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        let len = entries_len::<_, _, _, L>(self.iter());
+        len.encoded_len_with::<L>() + len
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S, W> Emit<W> for HashMap<K, V, S>
+where
+    K: Emit<W> + Emit<Vec<u8>>,
+    V: Emit<W> + Emit<Vec<u8>>,
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        let len = entries_len::<_, _, _, L>(self.iter());
+        if L::CANONICAL {
+            let emit = |(k, v): (&K, &V), b: &mut Vec<u8>| {
+                Emit::<Vec<u8>>::emit::<L::Inner>(k, b)?;
+                Emit::<Vec<u8>>::emit::<<L::Inner as Limit>::Next>(v, b)
+            };
+            emit_sorted::<L, _>(len, sorted_encodings(self.iter(), emit)?, buffer)
+        } else {
+            emit_entries::<_, _, _, L, _>(len, self.iter(), buffer)
+        }
+    }
+}
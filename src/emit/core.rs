@@ -3,9 +3,16 @@
 
 use alloc::vec::Vec;
 
+use super::{error::EmitError, len::EncodedLen, Limit, Canonical};
+
 pub trait RadiationBuffer {
     fn pos(&self) -> usize;
     fn write_at(&mut self, pos: usize, data: &[u8]);
+
+    /// The error if the bytes did not fit, see `CsBuffer`.
+    fn overflow(&self) -> Result<(), EmitError> {
+        Ok(())
+    }
 }
 
 impl RadiationBuffer for Vec<u8> {
@@ -20,29 +27,129 @@ impl RadiationBuffer for Vec<u8> {
 
 pub trait Emit<W>
 where
+    Self: EncodedLen,
     W: for<'a> Extend<&'a u8>,
 {
-    #[must_use]
-    fn chain(&self, mut buffer: W) -> W {
-        self.emit(&mut buffer);
-        buffer
+    /// Fails with `EmitError::BufferFull` if the buffer overflows.
+    fn chain(&self, mut buffer: W) -> Result<W, EmitError>
+    where
+        W: RadiationBuffer,
+    {
+        self.emit::<()>(&mut buffer)?;
+        buffer.overflow()?;
+        Ok(buffer)
+    }
+
+    /// Emit the canonical encoding, see `Canonical`.
+    fn chain_canonical(&self, mut buffer: W) -> Result<W, EmitError>
+    where
+        W: RadiationBuffer,
+    {
+        self.emit::<Canonical>(&mut buffer)?;
+        buffer.overflow()?;
+        Ok(buffer)
     }
 
-    fn emit(&self, buffer: &mut W);
+    /// Checks the same limits as `Absorb::absorb::<L>` does,
+    /// so the emitted bytes will not be rejected by the receiver.
+    /// The buffer which may overflow, e.g. `CsBuffer`, reports it
+    /// by `RadiationBuffer::overflow`, `chain` checks it.
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit;
+
+    /// Emits the value and returns its length, `Mutex<T>` and `RefCell<T>`
+    /// take one lock or borrow for both, so the length agrees with the bytes.
+    #[doc(hidden)]
+    fn emit_measured<L>(&self, buffer: &mut W) -> Result<usize, EmitError>
+    where
+        L: Limit,
+    {
+        self.emit::<L>(buffer)?;
+        Ok(self.encoded_len_with::<L>())
+    }
+
+    /// The elements of the array one after another, `u8` extends the buffer at once.
+    #[doc(hidden)]
+    fn emit_slice<L>(items: &[Self], buffer: &mut W) -> Result<(), EmitError>
+    where
+        Self: Sized,
+        L: Limit,
+    {
+        for v in items {
+            v.emit::<L>(buffer)?;
+        }
+        Ok(())
+    }
 }
 
+impl<T, W> Emit<W> for &T
+where
+    T: Emit<W>,
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        (**self).emit::<L>(buffer)
+    }
+
+    fn emit_measured<L>(&self, buffer: &mut W) -> Result<usize, EmitError>
+    where
+        L: Limit,
+    {
+        (**self).emit_measured::<L>(buffer)
+    }
+}
+
+/// Fixed capacity buffer, does not allocate. The bytes which does not fit
+/// are dropped, but counted, so `chain` and `as_slice` can report
+/// how many is required.
 pub struct CsBuffer<const SIZE: usize> {
     pos: usize,
     bytes: [u8; SIZE],
 }
 
+impl<const SIZE: usize> Default for CsBuffer<SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const SIZE: usize> CsBuffer<SIZE> {
+    pub const fn new() -> Self {
+        CsBuffer {
+            pos: 0,
+            bytes: [0; SIZE],
+        }
+    }
+
+    pub fn as_slice(&self) -> Result<&[u8], EmitError> {
+        if self.pos > SIZE {
+            Err(EmitError::BufferFull {
+                capacity: SIZE,
+                required: self.pos,
+            })
+        } else {
+            Ok(&self.bytes[..self.pos])
+        }
+    }
+}
+
 impl<const SIZE: usize> RadiationBuffer for CsBuffer<SIZE> {
     fn pos(&self) -> usize {
         self.pos
     }
 
     fn write_at(&mut self, pos: usize, data: &[u8]) {
-        self.bytes[pos..(pos + data.len())].clone_from_slice(data);
+        if let Some(bytes) = self.bytes.get_mut(pos..(pos + data.len())) {
+            bytes.clone_from_slice(data);
+        }
+    }
+
+    fn overflow(&self) -> Result<(), EmitError> {
+        self.as_slice().map(drop)
     }
 }
 
@@ -60,7 +167,9 @@ where
 {
     fn spec_extend(&mut self, iter: I) {
         for b in iter {
-            self.bytes[self.pos] = *b;
+            if let Some(byte) = self.bytes.get_mut(self.pos) {
+                *byte = *b;
+            }
             self.pos += 1;
         }
     }
@@ -73,7 +182,9 @@ where
 {
     default fn spec_extend(&mut self, iter: I) {
         for b in iter {
-            self.bytes[self.pos] = *b;
+            if let Some(byte) = self.bytes.get_mut(self.pos) {
+                *byte = *b;
+            }
             self.pos += 1;
         }
     }
@@ -85,7 +196,9 @@ impl<'a, const SIZE: usize> SpecExtend<'a, <&'a [u8] as IntoIterator>::IntoIter>
 {
     fn spec_extend(&mut self, iter: <&'a [u8] as IntoIterator>::IntoIter) {
         let new_pos = self.pos + iter.as_slice().len();
-        self.bytes[self.pos..new_pos].clone_from_slice(iter.as_slice());
+        if let Some(bytes) = self.bytes.get_mut(self.pos..new_pos) {
+            bytes.clone_from_slice(iter.as_slice());
+        }
         self.pos = new_pos;
     }
 }
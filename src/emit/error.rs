@@ -0,0 +1,82 @@
+// Copyright 2022 Vladislav Melnik
+// SPDX-License-Identifier: MIT
+
+use core::fmt;
+use alloc::string::{String, ToString};
+
+use super::super::LimitError;
+
+#[derive(Debug)]
+pub enum EmitError {
+    LengthOverflow {
+        length: usize,
+        maximum: usize,
+    },
+    BufferFull {
+        capacity: usize,
+        required: usize,
+    },
+    Limit(LimitError, &'static str),
+    /// The collection has equal elements, impossible in canonical mode.
+    DuplicateKey,
+    /// The time does not fit the encoding, e.g. `UnixTimestamp<Nanos>`
+    /// beyond the year 2262.
+    TimeOverflow,
+    /// The path or `OsString` is not UTF-8, which is required in strict mode,
+    /// or on the platform other than Unix.
+    NonUtf8Path,
+    /// The optional field is `None`, but the version of the layout requires it,
+    /// or the trailing optional field which follows it is present.
+    MissingField(&'static str),
+    /// The optional field is `Some`, but the version of the layout precedes it.
+    UnexpectedField(&'static str),
+    /// The length of the value changed after the length prefix was emitted,
+    /// e.g. `Mutex<T>` is written concurrently.
+    LengthChanged {
+        prefix: usize,
+        actual: usize,
+    },
+    Custom(String),
+}
+
+impl fmt::Display for EmitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EmitError::LengthOverflow { length, maximum } => {
+                write!(f, "length does not fit the prefix, {length} > {maximum}")
+            }
+            EmitError::BufferFull { capacity, required } => {
+                write!(f, "buffer is full, {required} > {capacity}")
+            }
+            EmitError::Limit(err, hint) => write!(f, "{hint}, {err}"),
+            EmitError::DuplicateKey => write!(f, "duplicate key"),
+            EmitError::TimeOverflow => write!(f, "time overflow"),
+            EmitError::NonUtf8Path => write!(f, "non utf-8 path"),
+            EmitError::MissingField(name) => write!(f, "missing field {name}"),
+            EmitError::UnexpectedField(name) => write!(f, "unexpected field {name}"),
+            EmitError::LengthChanged { prefix, actual } => {
+                write!(f, "length changed during emit, {actual} != {prefix}")
+            }
+            EmitError::Custom(custom) => write!(f, "custom: {custom}"),
+        }
+    }
+}
+
+#[cfg(any(feature = "std", feature = "codec"))]
+impl std::error::Error for EmitError {}
+
+#[cfg(any(feature = "std", feature = "codec"))]
+impl From<EmitError> for std::io::Error {
+    fn from(err: EmitError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, err)
+    }
+}
+
+impl EmitError {
+    pub fn custom<E>(e: E) -> Self
+    where
+        E: fmt::Display,
+    {
+        EmitError::Custom(e.to_string())
+    }
+}
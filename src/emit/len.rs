@@ -0,0 +1,72 @@
+// Copyright 2022 Vladislav Melnik
+// SPDX-License-Identifier: MIT
+
+use super::{core::RadiationBuffer, Limit};
+
+/// The number of bytes the value occupies when emitted.
+pub trait EncodedLen {
+    /// `Some` if every value of the type occupies the same number of bytes.
+    const SIZE: Option<usize> = None;
+
+    /// The number of bytes `Emit::chain` writes.
+    fn encoded_len(&self) -> usize {
+        self.encoded_len_with::<()>()
+    }
+
+    /// The number of bytes `Emit::emit::<L>` writes.
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit;
+}
+
+impl<T> EncodedLen for &T
+where
+    T: EncodedLen,
+{
+    const SIZE: Option<usize> = T::SIZE;
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        (**self).encoded_len_with::<L>()
+    }
+}
+
+/// The size of a sequence of values, `None` if any of them is not fixed.
+pub const fn size_sum(a: Option<usize>, b: Option<usize>) -> Option<usize> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a + b),
+        _ => None,
+    }
+}
+
+/// The size of one of alternatives, `None` if they are different.
+pub const fn size_same(a: Option<usize>, b: Option<usize>) -> Option<usize> {
+    match (a, b) {
+        (Some(a), Some(b)) if a == b => Some(a),
+        _ => None,
+    }
+}
+
+/// The buffer which only counts the bytes, the derive macro uses it
+/// to compute `EncodedLen` of the fields with custom emitters.
+#[derive(Default)]
+pub struct Counter(usize);
+
+impl RadiationBuffer for Counter {
+    fn pos(&self) -> usize {
+        self.0
+    }
+
+    fn write_at(&mut self, _: usize, _: &[u8]) {}
+}
+
+impl<'a> Extend<&'a u8> for Counter {
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = &'a u8>,
+    {
+        self.0 += iter.into_iter().count();
+    }
+}
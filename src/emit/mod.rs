@@ -1,17 +1,35 @@
 // Copyright 2022 Vladislav Melnik
 // SPDX-License-Identifier: MIT
 
-use super::{DynSized, Collection};
+use super::{
+    DynSized, Collection, Unordered, Le, Be, Prefixed, Limit, LittleEndian, BigEndian,
+    LengthPrefix, AsLengthPrefix, WithPrefix, Canonical, SeqView, RestView, VarU64, VarI64,
+};
+
+mod error;
+pub use self::error::EmitError;
+
+mod len;
+pub use self::len::{EncodedLen, Counter, size_sum, size_same};
 
 mod core;
-pub use self::core::{RadiationBuffer, Emit};
+pub use self::core::{RadiationBuffer, Emit, CsBuffer};
 
 mod primitives;
 
 mod atomics;
 
+mod pointers;
+
+mod varint;
+
 mod seq;
 
+mod collections;
+
+mod canonical;
+pub use self::canonical::{IsCanonical, is_canonical};
+
 /// implementations for some standard types
 #[cfg(feature = "std")]
 mod types;
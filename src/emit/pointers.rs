@@ -0,0 +1,307 @@
+// Copyright 2022 Vladislav Melnik
+// SPDX-License-Identifier: MIT
+
+use core::cell::{Cell, RefCell};
+use alloc::{boxed::Box, rc::Rc, sync::Arc};
+#[cfg(feature = "std")]
+use std::sync::{Mutex, PoisonError};
+
+use super::{
+    core::Emit,
+    error::EmitError,
+    len::EncodedLen,
+    seq::{slice_len, emit_rest},
+    Limit,
+};
+
+impl<T> EncodedLen for Rc<T>
+where
+    T: EncodedLen,
+{
+    const SIZE: Option<usize> = T::SIZE;
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        (**self).encoded_len_with::<L>()
+    }
+}
+
+impl<T, W> Emit<W> for Rc<T>
+where
+    T: Emit<W>,
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        (**self).emit::<L>(buffer)
+    }
+
+    fn emit_measured<L>(&self, buffer: &mut W) -> Result<usize, EmitError>
+    where
+        L: Limit,
+    {
+        (**self).emit_measured::<L>(buffer)
+    }
+}
+
+impl<T> EncodedLen for Arc<T>
+where
+    T: EncodedLen,
+{
+    const SIZE: Option<usize> = T::SIZE;
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        (**self).encoded_len_with::<L>()
+    }
+}
+
+impl<T, W> Emit<W> for Arc<T>
+where
+    T: Emit<W>,
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        (**self).emit::<L>(buffer)
+    }
+
+    fn emit_measured<L>(&self, buffer: &mut W) -> Result<usize, EmitError>
+    where
+        L: Limit,
+    {
+        (**self).emit_measured::<L>(buffer)
+    }
+}
+
+// the same encoding as `String`
+
+impl EncodedLen for Box<str> {
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        (**self).encoded_len_with::<L>()
+    }
+}
+
+impl<W> Emit<W> for Box<str>
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        (**self).emit::<L>(buffer)
+    }
+}
+
+impl EncodedLen for Rc<str> {
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        (**self).encoded_len_with::<L>()
+    }
+}
+
+impl<W> Emit<W> for Rc<str>
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        (**self).emit::<L>(buffer)
+    }
+}
+
+impl EncodedLen for Arc<str> {
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        (**self).encoded_len_with::<L>()
+    }
+}
+
+impl<W> Emit<W> for Arc<str>
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        (**self).emit::<L>(buffer)
+    }
+}
+
+// the same encoding as `Box<[T]>`
+
+impl<T> EncodedLen for Rc<[T]>
+where
+    T: EncodedLen,
+{
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        slice_len::<_, L::Inner>(self)
+    }
+}
+
+impl<T, W> Emit<W> for Rc<[T]>
+where
+    T: Emit<W>,
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        emit_rest::<_, L, _>(self, buffer)
+    }
+}
+
+impl<T> EncodedLen for Arc<[T]>
+where
+    T: EncodedLen,
+{
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        slice_len::<_, L::Inner>(self)
+    }
+}
+
+impl<T, W> Emit<W> for Arc<[T]>
+where
+    T: Emit<W>,
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        emit_rest::<_, L, _>(self, buffer)
+    }
+}
+
+impl<T> EncodedLen for Cell<T>
+where
+    T: EncodedLen + Copy,
+{
+    const SIZE: Option<usize> = T::SIZE;
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        self.get().encoded_len_with::<L>()
+    }
+}
+
+impl<T, W> Emit<W> for Cell<T>
+where
+    T: Emit<W> + Copy,
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        self.get().emit::<L>(buffer)
+    }
+}
+
+// zero if the value is mutably borrowed, emit fails anyway
+impl<T> EncodedLen for RefCell<T>
+where
+    T: EncodedLen,
+{
+    const SIZE: Option<usize> = T::SIZE;
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        self.try_borrow()
+            .map_or(0, |value| value.encoded_len_with::<L>())
+    }
+}
+
+impl<T, W> Emit<W> for RefCell<T>
+where
+    T: Emit<W>,
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        self.emit_measured::<L>(buffer).map(drop)
+    }
+
+    fn emit_measured<L>(&self, buffer: &mut W) -> Result<usize, EmitError>
+    where
+        L: Limit,
+    {
+        self.try_borrow()
+            .map_err(EmitError::custom)?
+            .emit_measured::<L>(buffer)
+    }
+}
+
+// the length of the value is known even if the mutex is poisoned
+#[cfg(feature = "std")]
+impl<T> EncodedLen for Mutex<T>
+where
+    T: EncodedLen,
+{
+    const SIZE: Option<usize> = T::SIZE;
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .encoded_len_with::<L>()
+    }
+}
+
+/// Locks the mutex, the poisoned mutex is an error.
+#[cfg(feature = "std")]
+impl<T, W> Emit<W> for Mutex<T>
+where
+    T: Emit<W>,
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        self.emit_measured::<L>(buffer).map(drop)
+    }
+
+    fn emit_measured<L>(&self, buffer: &mut W) -> Result<usize, EmitError>
+    where
+        L: Limit,
+    {
+        self.lock()
+            .map_err(EmitError::custom)?
+            .emit_measured::<L>(buffer)
+    }
+}
@@ -1,35 +1,117 @@
 // Copyright 2022 Vladislav Melnik
 // SPDX-License-Identifier: MIT
 
-use core::marker::PhantomData;
+use core::{
+    marker::PhantomData,
+    mem,
+    num::{
+        NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU128, NonZeroUsize, NonZeroI8,
+        NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128,
+    },
+};
 use alloc::boxed::Box;
 
-use super::core::Emit;
+use nom::number::Endianness;
+
+use super::{
+    core::Emit,
+    error::EmitError,
+    len::{EncodedLen, size_sum},
+    Le, Be, Limit, LittleEndian, BigEndian,
+};
+
+macro_rules! extend_ordered {
+    ($buffer:expr, $value:expr, $endianness:expr) => {
+        match $endianness {
+            Endianness::Big => $buffer.extend(&$value.to_be_bytes()),
+            Endianness::Little => $buffer.extend(&$value.to_le_bytes()),
+            Endianness::Native => $buffer.extend(&$value.to_ne_bytes()),
+        }
+    };
+}
+
+impl<T> EncodedLen for PhantomData<T> {
+    const SIZE: Option<usize> = Some(0);
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        0
+    }
+}
 
 impl<T, W> Emit<W> for PhantomData<T>
 where
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, _: &mut W) {}
+    fn emit<L>(&self, _: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        Ok(())
+    }
+}
+
+impl EncodedLen for () {
+    const SIZE: Option<usize> = Some(0);
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        0
+    }
 }
 
 impl<W> Emit<W> for ()
 where
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, _: &mut W) {}
+    fn emit<L>(&self, _: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        Ok(())
+    }
+}
+
+impl EncodedLen for bool {
+    const SIZE: Option<usize> = Some(1);
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        1
+    }
 }
 
 impl<W> Emit<W> for bool
 where
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
         if *self {
             buffer.extend(Some(&0xff));
         } else {
             buffer.extend(Some(&0x00));
         }
+        Ok(())
+    }
+}
+
+impl EncodedLen for i8 {
+    const SIZE: Option<usize> = Some(1);
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        1
     }
 }
 
@@ -37,8 +119,23 @@ impl<W> Emit<W> for i8
 where
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
         buffer.extend(Some(&(*self as u8)));
+        Ok(())
+    }
+}
+
+impl EncodedLen for u8 {
+    const SIZE: Option<usize> = Some(1);
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        1
     }
 }
 
@@ -46,8 +143,31 @@ impl<W> Emit<W> for u8
 where
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
         buffer.extend(Some(self));
+        Ok(())
+    }
+
+    fn emit_slice<L>(items: &[Self], buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        buffer.extend(items);
+        Ok(())
+    }
+}
+
+impl EncodedLen for i16 {
+    const SIZE: Option<usize> = Some(mem::size_of::<i16>());
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        mem::size_of::<i16>()
     }
 }
 
@@ -55,8 +175,23 @@ impl<W> Emit<W> for i16
 where
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
-        buffer.extend(&self.to_be_bytes());
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        extend_ordered!(buffer, self, L::ENDIANNESS);
+        Ok(())
+    }
+}
+
+impl EncodedLen for u16 {
+    const SIZE: Option<usize> = Some(mem::size_of::<u16>());
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        mem::size_of::<u16>()
     }
 }
 
@@ -64,8 +199,23 @@ impl<W> Emit<W> for u16
 where
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
-        buffer.extend(&self.to_be_bytes());
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        extend_ordered!(buffer, self, L::ENDIANNESS);
+        Ok(())
+    }
+}
+
+impl EncodedLen for i32 {
+    const SIZE: Option<usize> = Some(mem::size_of::<i32>());
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        mem::size_of::<i32>()
     }
 }
 
@@ -73,8 +223,23 @@ impl<W> Emit<W> for i32
 where
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
-        buffer.extend(&self.to_be_bytes());
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        extend_ordered!(buffer, self, L::ENDIANNESS);
+        Ok(())
+    }
+}
+
+impl EncodedLen for u32 {
+    const SIZE: Option<usize> = Some(mem::size_of::<u32>());
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        mem::size_of::<u32>()
     }
 }
 
@@ -82,8 +247,23 @@ impl<W> Emit<W> for u32
 where
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
-        buffer.extend(&self.to_be_bytes());
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        extend_ordered!(buffer, self, L::ENDIANNESS);
+        Ok(())
+    }
+}
+
+impl EncodedLen for i64 {
+    const SIZE: Option<usize> = Some(mem::size_of::<i64>());
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        mem::size_of::<i64>()
     }
 }
 
@@ -91,8 +271,23 @@ impl<W> Emit<W> for i64
 where
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
-        buffer.extend(&self.to_be_bytes());
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        extend_ordered!(buffer, self, L::ENDIANNESS);
+        Ok(())
+    }
+}
+
+impl EncodedLen for u64 {
+    const SIZE: Option<usize> = Some(mem::size_of::<u64>());
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        mem::size_of::<u64>()
     }
 }
 
@@ -100,8 +295,71 @@ impl<W> Emit<W> for u64
 where
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
-        buffer.extend(&self.to_be_bytes());
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        extend_ordered!(buffer, self, L::ENDIANNESS);
+        Ok(())
+    }
+}
+
+impl EncodedLen for i128 {
+    const SIZE: Option<usize> = Some(mem::size_of::<i128>());
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        mem::size_of::<i128>()
+    }
+}
+
+impl<W> Emit<W> for i128
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        extend_ordered!(buffer, self, L::ENDIANNESS);
+        Ok(())
+    }
+}
+
+impl EncodedLen for u128 {
+    const SIZE: Option<usize> = Some(mem::size_of::<u128>());
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        mem::size_of::<u128>()
+    }
+}
+
+impl<W> Emit<W> for u128
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        extend_ordered!(buffer, self, L::ENDIANNESS);
+        Ok(())
+    }
+}
+
+impl EncodedLen for f32 {
+    const SIZE: Option<usize> = Some(mem::size_of::<f32>());
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        mem::size_of::<f32>()
     }
 }
 
@@ -109,8 +367,28 @@ impl<W> Emit<W> for f32
 where
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
-        buffer.extend(&self.to_be_bytes());
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        let value = if L::CANONICAL && self.is_nan() {
+            f32::NAN
+        } else {
+            *self
+        };
+        extend_ordered!(buffer, value, L::ENDIANNESS);
+        Ok(())
+    }
+}
+
+impl EncodedLen for f64 {
+    const SIZE: Option<usize> = Some(mem::size_of::<f64>());
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        mem::size_of::<f64>()
     }
 }
 
@@ -118,30 +396,205 @@ impl<W> Emit<W> for f64
 where
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
-        buffer.extend(&self.to_be_bytes());
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        let value = if L::CANONICAL && self.is_nan() {
+            f64::NAN
+        } else {
+            *self
+        };
+        extend_ordered!(buffer, value, L::ENDIANNESS);
+        Ok(())
     }
 }
 
-impl<A, B, W> Emit<W> for (A, B)
+impl EncodedLen for char {
+    const SIZE: Option<usize> = Some(mem::size_of::<u32>());
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        mem::size_of::<u32>()
+    }
+}
+
+impl<W> Emit<W> for char
 where
-    A: Emit<W>,
-    B: Emit<W>,
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
-        let (a, b) = self;
-        a.emit(buffer);
-        b.emit(buffer);
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        u32::from(*self).emit::<L>(buffer)
     }
 }
 
-impl<const S: usize, W> Emit<W> for [u8; S]
+macro_rules! impl_non_zero {
+    ($($t:ty => $u:ty),+) => {
+        $(
+            impl EncodedLen for $t {
+                const SIZE: Option<usize> = <$u as EncodedLen>::SIZE;
+
+                fn encoded_len_with<L>(&self) -> usize
+                where
+                    L: Limit,
+                {
+                    self.get().encoded_len_with::<L>()
+                }
+            }
+
+            impl<W> Emit<W> for $t
+            where
+                W: for<'a> Extend<&'a u8>,
+            {
+                fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+                where
+                    L: Limit,
+                {
+                    self.get().emit::<L>(buffer)
+                }
+            }
+        )+
+    };
+}
+
+impl_non_zero!(
+    NonZeroU8 => u8,
+    NonZeroU16 => u16,
+    NonZeroU32 => u32,
+    NonZeroU64 => u64,
+    NonZeroU128 => u128,
+    NonZeroUsize => usize,
+    NonZeroI8 => i8,
+    NonZeroI16 => i16,
+    NonZeroI32 => i32,
+    NonZeroI64 => i64,
+    NonZeroI128 => i128
+);
+
+macro_rules! size_tuple {
+    () => {
+        Some(0)
+    };
+    ($t:ident $(, $ts:ident)*) => {
+        size_sum($t::SIZE, size_tuple!($($ts),*))
+    };
+}
+
+macro_rules! len_tuple {
+    ($l:ty;) => {
+        0
+    };
+    ($l:ty; $v:ident $(, $vs:ident)*) => {
+        $v.encoded_len_with::<$l>() + len_tuple!(<$l as Limit>::Next; $($vs),*)
+    };
+}
+
+macro_rules! emit_tuple {
+    ($buffer:ident, $l:ty;) => {};
+    ($buffer:ident, $l:ty; $v:ident $(, $vs:ident)*) => {
+        $v.emit::<$l>($buffer)?;
+        emit_tuple!($buffer, <$l as Limit>::Next; $($vs),*);
+    };
+}
+
+// each next element is emitted with the next limit, like fields of a structure
+macro_rules! impl_tuple {
+    ($($t:ident $v:ident),+) => {
+        impl<$($t),+> EncodedLen for ($($t,)+)
+        where
+            $($t: EncodedLen,)+
+        {
+            const SIZE: Option<usize> = size_tuple!($($t),+);
+
+            fn encoded_len_with<L>(&self) -> usize
+            where
+                L: Limit,
+            {
+                let ($($v,)+) = self;
+                len_tuple!(L; $($v),+)
+            }
+        }
+
+        impl<$($t,)+ W> Emit<W> for ($($t,)+)
+        where
+            $($t: Emit<W>,)+
+            W: for<'a> Extend<&'a u8>,
+        {
+            fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+            where
+                L: Limit,
+            {
+                let ($($v,)+) = self;
+                emit_tuple!(buffer, L; $($v),+);
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_tuple!(A a);
+impl_tuple!(A a, B b);
+impl_tuple!(A a, B b, C c);
+impl_tuple!(A a, B b, C c, D d);
+impl_tuple!(A a, B b, C c, D d, E e);
+impl_tuple!(A a, B b, C c, D d, E e, F f);
+impl_tuple!(A a, B b, C c, D d, E e, F f, G g);
+impl_tuple!(A a, B b, C c, D d, E e, F f, G g, H h);
+impl_tuple!(A a, B b, C c, D d, E e, F f, G g, H h, I i);
+impl_tuple!(A a, B b, C c, D d, E e, F f, G g, H h, I i, J j);
+impl_tuple!(A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k);
+impl_tuple!(A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k, M m);
+
+impl<T, const S: usize> EncodedLen for [T; S]
+where
+    T: EncodedLen,
+{
+    const SIZE: Option<usize> = match T::SIZE {
+        Some(size) => Some(size * S),
+        None => None,
+    };
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        match T::SIZE {
+            Some(size) => size * S,
+            None => self.iter().map(T::encoded_len_with::<L>).sum(),
+        }
+    }
+}
+
+impl<T, W, const S: usize> Emit<W> for [T; S]
 where
+    T: Emit<W>,
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
-        buffer.extend(self);
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        T::emit_slice::<L>(self, buffer)
+    }
+}
+
+impl<T> EncodedLen for Option<T>
+where
+    T: EncodedLen,
+{
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        match self {
+            None => 1,
+            Some(v) => 1 + v.encoded_len_with::<L>(),
+        }
     }
 }
 
@@ -150,43 +603,143 @@ where
     T: Emit<W>,
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
         match self {
-            None => false.emit(buffer),
+            None => false.emit::<()>(buffer),
             Some(v) => {
-                true.emit(buffer);
-                v.emit(buffer);
+                true.emit::<()>(buffer)?;
+                v.emit::<L>(buffer)
             }
         }
     }
 }
 
+impl<T, E> EncodedLen for Result<T, E>
+where
+    T: EncodedLen,
+    E: EncodedLen,
+{
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        match self {
+            Ok(v) => 1 + v.encoded_len_with::<L>(),
+            Err(v) => 1 + v.encoded_len_with::<L>(),
+        }
+    }
+}
+
 impl<T, E, W> Emit<W> for Result<T, E>
 where
     T: Emit<W>,
     E: Emit<W>,
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
         match self {
             Ok(v) => {
-                true.emit(buffer);
-                v.emit(buffer);
+                true.emit::<()>(buffer)?;
+                v.emit::<L>(buffer)
             }
             Err(v) => {
-                false.emit(buffer);
-                v.emit(buffer);
+                0xfe_u8.emit::<()>(buffer)?;
+                v.emit::<L>(buffer)
             }
         }
     }
 }
 
+impl<T> EncodedLen for Box<T>
+where
+    T: EncodedLen,
+{
+    const SIZE: Option<usize> = T::SIZE;
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        (**self).encoded_len_with::<L>()
+    }
+}
+
 impl<T, W> Emit<W> for Box<T>
 where
     T: Emit<W>,
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
-        (**self).emit(buffer);
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        (**self).emit::<L>(buffer)
+    }
+
+    fn emit_measured<L>(&self, buffer: &mut W) -> Result<usize, EmitError>
+    where
+        L: Limit,
+    {
+        (**self).emit_measured::<L>(buffer)
+    }
+}
+
+impl<T> EncodedLen for Le<T>
+where
+    T: EncodedLen,
+{
+    const SIZE: Option<usize> = T::SIZE;
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        self.0.encoded_len_with::<LittleEndian<L>>()
+    }
+}
+
+impl<T, W> Emit<W> for Le<T>
+where
+    T: Emit<W>,
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        self.0.emit::<LittleEndian<L>>(buffer)
+    }
+}
+
+impl<T> EncodedLen for Be<T>
+where
+    T: EncodedLen,
+{
+    const SIZE: Option<usize> = T::SIZE;
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        self.0.encoded_len_with::<BigEndian<L>>()
+    }
+}
+
+impl<T, W> Emit<W> for Be<T>
+where
+    T: Emit<W>,
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        self.0.emit::<BigEndian<L>>(buffer)
     }
 }
@@ -1,19 +1,125 @@
 // Copyright 2022 Vladislav Melnik
 // SPDX-License-Identifier: MIT
 
-use alloc::{string::String, boxed::Box, vec::Vec};
+use alloc::{
+    string::String,
+    boxed::Box,
+    vec::Vec,
+    borrow::{Cow, ToOwned},
+};
 
 use super::{
-    core::{RadiationBuffer, Emit},
-    DynSized, Collection,
+    core::Emit,
+    error::EmitError,
+    len::{EncodedLen, size_sum},
+    collections::emit_unordered,
+    varint::emit_var_u64,
+    DynSized, Collection, Unordered, Prefixed, SeqView, RestView, Limit, LengthPrefix,
+    AsLengthPrefix, WithPrefix,
 };
 
+fn check<L>(length: usize) -> Result<usize, EmitError>
+where
+    L: Limit,
+{
+    L::check(length).map_err(|e| EmitError::Limit(e, L::DESCRIPTION))
+}
+
+pub(super) fn slice_len<T, L>(slice: &[T]) -> usize
+where
+    T: EncodedLen,
+    L: Limit,
+{
+    match T::SIZE {
+        Some(size) => size * slice.len(),
+        None => slice.iter().map(T::encoded_len_with::<L>).sum(),
+    }
+}
+
+/// The length prefix and the elements, the encoding of `Vec<T>`.
+pub(super) fn emit_prefixed<T, L, W>(slice: &[T], buffer: &mut W) -> Result<(), EmitError>
+where
+    T: Emit<W>,
+    L: Limit,
+    W: for<'a> Extend<&'a u8>,
+{
+    let len = slice_len::<_, L::Inner>(slice);
+    len.emit::<L>(buffer)?;
+    if T::SIZE.is_some() {
+        return T::emit_slice::<L::Inner>(slice, buffer);
+    }
+    let mut actual = 0;
+    for v in slice {
+        actual += v.emit_measured::<L::Inner>(buffer)?;
+    }
+    same_len(len, actual)
+}
+
+/// The value changed after its length was emitted, e.g. `Mutex<T>`.
+pub(super) fn same_len(prefix: usize, actual: usize) -> Result<(), EmitError> {
+    if prefix != actual {
+        return Err(EmitError::LengthChanged { prefix, actual });
+    }
+    Ok(())
+}
+
+/// The elements without the length, the encoding of `Box<[T]>`.
+pub(super) fn emit_rest<T, L, W>(slice: &[T], buffer: &mut W) -> Result<(), EmitError>
+where
+    T: Emit<W>,
+    L: Limit,
+    W: for<'a> Extend<&'a u8>,
+{
+    check::<L>(slice_len::<_, L::Inner>(slice))?;
+    for i in slice {
+        i.emit::<L::Inner>(buffer)?;
+    }
+    Ok(())
+}
+
+impl EncodedLen for usize {
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        L::PREFIX.encoded_len(*self as u64)
+    }
+}
+
 impl<W> Emit<W> for usize
 where
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
-        (*self as u32).emit(buffer);
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        let length = check::<L>(*self)?;
+        if length as u64 > L::PREFIX.maximum() {
+            return Err(EmitError::LengthOverflow {
+                length,
+                maximum: L::PREFIX.maximum() as usize,
+            });
+        }
+        match L::PREFIX {
+            LengthPrefix::U8 => (length as u8).emit::<L>(buffer),
+            LengthPrefix::U16 => (length as u16).emit::<L>(buffer),
+            LengthPrefix::U32 => (length as u32).emit::<L>(buffer),
+            LengthPrefix::U64 => (length as u64).emit::<L>(buffer),
+            LengthPrefix::Var => {
+                emit_var_u64(length as u64, buffer);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl EncodedLen for str {
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        self.len().encoded_len_with::<L>() + self.len()
     }
 }
 
@@ -21,10 +127,44 @@ impl<W> Emit<W> for str
 where
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
         let bytes = self.as_bytes();
-        bytes.len().emit(buffer);
+        bytes.len().emit::<L>(buffer)?;
         buffer.extend(bytes);
+        Ok(())
+    }
+}
+
+impl EncodedLen for &str {
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        (**self).encoded_len_with::<L>()
+    }
+}
+
+impl<W> Emit<W> for &str
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        (**self).emit::<L>(buffer)
+    }
+}
+
+impl EncodedLen for String {
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        (**self).encoded_len_with::<L>()
     }
 }
 
@@ -32,22 +172,84 @@ impl<W> Emit<W> for String
 where
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
-        (**self).emit(buffer);
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        (**self).emit::<L>(buffer)
+    }
+}
+
+impl<'a, B> EncodedLen for Cow<'a, B>
+where
+    B: ToOwned + ?Sized,
+    for<'b> &'b B: EncodedLen,
+{
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        (&**self).encoded_len_with::<L>()
+    }
+}
+
+/// Borrowed or owned, the encoding is the same, the one of `&B`.
+impl<'a, B, W> Emit<W> for Cow<'a, B>
+where
+    B: ToOwned + ?Sized,
+    for<'b> &'b B: Emit<W>,
+    W: for<'b> Extend<&'b u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        (&**self).emit::<L>(buffer)
+    }
+}
+
+impl<T> EncodedLen for DynSized<T>
+where
+    T: EncodedLen,
+{
+    const SIZE: Option<usize> = size_sum(usize::SIZE, T::SIZE);
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        let len = self.0.encoded_len_with::<L::Inner>();
+        len.encoded_len_with::<L>() + len
     }
 }
 
 impl<T, W> Emit<W> for DynSized<T>
 where
     T: Emit<W>,
-    W: for<'a> Extend<&'a u8> + RadiationBuffer,
+    W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
-        let pos = buffer.pos();
-        0usize.emit(buffer);
-        self.0.emit(buffer);
-        let len = buffer.pos() - pos - 4;
-        buffer.write_at(pos, &(len as u32).to_be_bytes());
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        let len = self.0.encoded_len_with::<L::Inner>();
+        len.emit::<L>(buffer)?;
+        if T::SIZE.is_some() {
+            return self.0.emit::<L::Inner>(buffer);
+        }
+        same_len(len, self.0.emit_measured::<L::Inner>(buffer)?)
+    }
+}
+
+impl<T> EncodedLen for Box<[T]>
+where
+    T: EncodedLen,
+{
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        slice_len::<_, L::Inner>(self)
     }
 }
 
@@ -57,10 +259,11 @@ where
     T: Emit<W>,
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
-        for i in self.iter() {
-            i.emit(buffer);
-        }
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        emit_rest::<_, L, _>(self, buffer)
     }
 }
 
@@ -70,18 +273,66 @@ where
     T: Emit<W>,
     W: for<'a> Extend<&'a u8>,
 {
-    default fn emit(&self, buffer: &mut W) {
-        for i in self.iter() {
-            i.emit(buffer);
-        }
+    default fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        emit_rest::<_, L, _>(self, buffer)
     }
 }
 
 // TODO: proper specialization
 #[cfg(feature = "nightly")]
 impl Emit<Vec<u8>> for Box<[u8]> {
-    fn emit(&self, buffer: &mut Vec<u8>) {
+    fn emit<L>(&self, buffer: &mut Vec<u8>) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        check::<L>(self.len())?;
         buffer.extend_from_slice(self);
+        Ok(())
+    }
+}
+
+// no `[T]`, it would be ambiguous, `&[T]` is prefixed like `Vec<T>`,
+// while `Box<[T]>` is not
+impl<T> EncodedLen for &[T]
+where
+    T: EncodedLen,
+{
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        let len = slice_len::<_, L::Inner>(self);
+        len.encoded_len_with::<L>() + len
+    }
+}
+
+/// The same encoding as `Vec<T>`, e.g. the field `&'pa [u8]`.
+impl<T, W> Emit<W> for &[T]
+where
+    T: Emit<W>,
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        emit_prefixed::<_, L, _>(self, buffer)
+    }
+}
+
+impl<T> EncodedLen for Vec<T>
+where
+    T: EncodedLen,
+{
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        let len = slice_len::<_, L::Inner>(self);
+        len.encoded_len_with::<L>() + len
     }
 }
 
@@ -89,16 +340,13 @@ impl Emit<Vec<u8>> for Box<[u8]> {
 impl<T, W> Emit<W> for Vec<T>
 where
     T: Emit<W>,
-    W: for<'a> Extend<&'a u8> + RadiationBuffer,
+    W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
-        let pos = buffer.pos();
-        0usize.emit(buffer);
-        for v in self {
-            v.emit(buffer);
-        }
-        let len = buffer.pos() - pos - 4;
-        buffer.write_at(pos, &(len as u32).to_be_bytes());
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        emit_prefixed::<_, L, _>(self, buffer)
     }
 }
 
@@ -106,28 +354,42 @@ where
 impl<T, W> Emit<W> for Vec<T>
 where
     T: Emit<W>,
-    W: for<'a> Extend<&'a u8> + RadiationBuffer,
+    W: for<'a> Extend<&'a u8>,
 {
-    default fn emit(&self, buffer: &mut W) {
-        let pos = buffer.pos();
-        0usize.emit(buffer);
-        for v in self {
-            v.emit(buffer);
-        }
-        let len = buffer.pos() - pos - 4;
-        buffer.write_at(pos, &(len as u32).to_be_bytes());
+    default fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        emit_prefixed::<_, L, _>(self, buffer)
     }
 }
 
 // TODO: proper specialization
 #[cfg(feature = "nightly")]
 impl Emit<Vec<u8>> for Vec<u8> {
-    fn emit(&self, buffer: &mut Vec<u8>) {
-        let pos = buffer.pos();
-        0usize.emit(buffer);
+    fn emit<L>(&self, buffer: &mut Vec<u8>) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        self.len().emit::<L>(buffer)?;
         buffer.extend_from_slice(self);
-        let len = buffer.pos() - pos - 4;
-        buffer.write_at(pos, &(len as u32).to_be_bytes());
+        Ok(())
+    }
+}
+
+impl<C> EncodedLen for Collection<C>
+where
+    C: IntoIterator + Clone,
+    C::Item: EncodedLen,
+{
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        C::clone(&self.0)
+            .into_iter()
+            .map(|v| v.encoded_len_with::<L::Inner>())
+            .sum()
     }
 }
 
@@ -135,11 +397,123 @@ impl<C, W> Emit<W> for Collection<C>
 where
     C: IntoIterator + Clone,
     C::Item: Emit<W>,
-    W: for<'a> Extend<&'a u8> + RadiationBuffer,
+    W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        check::<L>(self.encoded_len_with::<L>())?;
         for v in C::clone(&self.0) {
-            v.emit(buffer);
+            v.emit::<L::Inner>(buffer)?;
         }
+        Ok(())
+    }
+}
+
+impl<C> EncodedLen for Unordered<C>
+where
+    C: IntoIterator + Clone,
+    C::Item: EncodedLen,
+{
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        C::clone(&self.0)
+            .into_iter()
+            .map(|v| v.encoded_len_with::<L::Inner>())
+            .sum()
+    }
+}
+
+impl<C, W> Emit<W> for Unordered<C>
+where
+    C: IntoIterator + Clone,
+    C::Item: Emit<W> + Emit<Vec<u8>>,
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        check::<L>(self.encoded_len_with::<L>())?;
+        emit_unordered::<_, L::Inner, _>(C::clone(&self.0), buffer)
+    }
+}
+
+impl<P, T> EncodedLen for Prefixed<P, T>
+where
+    P: AsLengthPrefix,
+    T: EncodedLen,
+{
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        self.0.encoded_len_with::<WithPrefix<P, L>>()
+    }
+}
+
+impl<P, T, W> Emit<W> for Prefixed<P, T>
+where
+    P: AsLengthPrefix,
+    T: Emit<W>,
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        self.0.emit::<WithPrefix<P, L>>(buffer)
+    }
+}
+
+impl<'pa, T> EncodedLen for SeqView<'pa, T> {
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        let len = self.as_bytes().len();
+        len.encoded_len_with::<L>() + len
+    }
+}
+
+/// The elements are emitted as they were absorbed.
+impl<'pa, T, W> Emit<W> for SeqView<'pa, T>
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        self.as_bytes().len().emit::<L>(buffer)?;
+        buffer.extend(self.as_bytes());
+        Ok(())
+    }
+}
+
+impl<'pa, T> EncodedLen for RestView<'pa, T> {
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        self.as_bytes().len()
+    }
+}
+
+/// The elements are emitted as they were absorbed.
+impl<'pa, T, W> Emit<W> for RestView<'pa, T>
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        check::<L>(self.as_bytes().len())?;
+        buffer.extend(self.as_bytes());
+        Ok(())
     }
 }
@@ -1,22 +1,471 @@
-use std::net::{SocketAddr, IpAddr};
+use std::{
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    time::{Duration, SystemTime},
+};
 
-use super::core::Emit;
+use super::{
+    core::Emit,
+    error::EmitError,
+    len::EncodedLen,
+    Limit,
+    super::{WithScope, UnixTimestamp, Precision, NANOS_PER_SECOND},
+};
 
-impl<W> Emit<W> for SocketAddr
+// the addresses and ports are in network byte order regardless of `L`
+
+impl EncodedLen for Ipv4Addr {
+    const SIZE: Option<usize> = Some(4);
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        4
+    }
+}
+
+impl<W> Emit<W> for Ipv4Addr
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        buffer.extend(&self.octets());
+        Ok(())
+    }
+}
+
+impl EncodedLen for Ipv6Addr {
+    const SIZE: Option<usize> = Some(16);
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        16
+    }
+}
+
+impl<W> Emit<W> for Ipv6Addr
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        buffer.extend(&self.octets());
+        Ok(())
+    }
+}
+
+impl EncodedLen for IpAddr {
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        match self {
+            IpAddr::V4(ip) => 1 + ip.encoded_len(),
+            IpAddr::V6(ip) => 1 + ip.encoded_len(),
+        }
+    }
+}
+
+impl<W> Emit<W> for IpAddr
 where
     W: for<'a> Extend<&'a u8>,
 {
-    fn emit(&self, buffer: &mut W) {
-        match self.ip() {
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        match self {
+            IpAddr::V4(ip) => {
+                4u8.emit::<()>(buffer)?;
+                ip.emit::<()>(buffer)
+            }
             IpAddr::V6(ip) => {
-                6u8.emit(buffer);
-                ip.octets().emit(buffer);
+                6u8.emit::<()>(buffer)?;
+                ip.emit::<()>(buffer)
             }
-            IpAddr::V4(ip) => {
-                4u8.emit(buffer);
-                ip.octets().emit(buffer);
+        }
+    }
+}
+
+impl EncodedLen for SocketAddrV4 {
+    const SIZE: Option<usize> = Some(4 + 2);
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        4 + 2
+    }
+}
+
+impl<W> Emit<W> for SocketAddrV4
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        self.ip().emit::<()>(buffer)?;
+        self.port().emit::<()>(buffer)
+    }
+}
+
+impl EncodedLen for SocketAddrV6 {
+    const SIZE: Option<usize> = Some(16 + 2);
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        16 + 2
+    }
+}
+
+/// Drops `flowinfo` and `scope_id`, see `WithScope`.
+impl<W> Emit<W> for SocketAddrV6
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        self.ip().emit::<()>(buffer)?;
+        self.port().emit::<()>(buffer)
+    }
+}
+
+impl EncodedLen for WithScope<SocketAddrV6> {
+    const SIZE: Option<usize> = Some(16 + 2 + 4 + 4);
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        16 + 2 + 4 + 4
+    }
+}
+
+impl<W> Emit<W> for WithScope<SocketAddrV6>
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        self.0.emit::<()>(buffer)?;
+        self.0.flowinfo().emit::<()>(buffer)?;
+        self.0.scope_id().emit::<()>(buffer)
+    }
+}
+
+impl EncodedLen for SocketAddr {
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        match self {
+            SocketAddr::V4(addr) => 1 + addr.encoded_len(),
+            SocketAddr::V6(addr) => 1 + addr.encoded_len(),
+        }
+    }
+}
+
+impl<W> Emit<W> for SocketAddr
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        match self {
+            SocketAddr::V4(addr) => {
+                4u8.emit::<()>(buffer)?;
+                addr.emit::<()>(buffer)
+            }
+            SocketAddr::V6(addr) => {
+                6u8.emit::<()>(buffer)?;
+                addr.emit::<()>(buffer)
             }
         }
-        self.port().emit(buffer);
+    }
+}
+
+impl EncodedLen for WithScope<SocketAddr> {
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        match self.0 {
+            SocketAddr::V4(addr) => 1 + addr.encoded_len(),
+            SocketAddr::V6(addr) => 1 + WithScope(addr).encoded_len(),
+        }
+    }
+}
+
+impl<W> Emit<W> for WithScope<SocketAddr>
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        match self.0 {
+            SocketAddr::V4(addr) => {
+                4u8.emit::<()>(buffer)?;
+                addr.emit::<()>(buffer)
+            }
+            SocketAddr::V6(addr) => {
+                6u8.emit::<()>(buffer)?;
+                WithScope(addr).emit::<()>(buffer)
+            }
+        }
+    }
+}
+
+impl EncodedLen for Duration {
+    const SIZE: Option<usize> = Some(8 + 4);
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        8 + 4
+    }
+}
+
+impl<W> Emit<W> for Duration
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        self.as_secs().emit::<L>(buffer)?;
+        self.subsec_nanos().emit::<L>(buffer)
+    }
+}
+
+/// The seconds since the Unix epoch, negative before it, and
+/// the nanoseconds after them, the inverse of `unix_time` of absorb.
+fn unix_secs_nanos(time: &SystemTime) -> Result<(i64, u32), EmitError> {
+    let (secs, nanos) = match time.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(since) => (i64::try_from(since.as_secs()), since.subsec_nanos()),
+        Err(err) => {
+            let before = err.duration();
+            match before.subsec_nanos() {
+                0 => (i64::try_from(before.as_secs()).map(|s| -s), 0),
+                nanos => (
+                    i64::try_from(before.as_secs() + 1).map(|s| -s),
+                    NANOS_PER_SECOND - nanos,
+                ),
+            }
+        }
+    };
+    secs.map(|secs| (secs, nanos))
+        .map_err(|_| EmitError::TimeOverflow)
+}
+
+impl EncodedLen for SystemTime {
+    const SIZE: Option<usize> = Some(8 + 4);
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        8 + 4
+    }
+}
+
+impl<W> Emit<W> for SystemTime
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        let (secs, nanos) = unix_secs_nanos(self)?;
+        secs.emit::<L>(buffer)?;
+        nanos.emit::<L>(buffer)
+    }
+}
+
+impl<P> EncodedLen for UnixTimestamp<P> {
+    const SIZE: Option<usize> = Some(8);
+
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        8
+    }
+}
+
+impl<P, W> Emit<W> for UnixTimestamp<P>
+where
+    P: Precision,
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        let (secs, nanos) = unix_secs_nanos(&self.0)?;
+        let units = secs
+            .checked_mul(P::PER_SECOND as i64)
+            .and_then(|units| {
+                units.checked_add((nanos as u64 * P::PER_SECOND / NANOS_PER_SECOND as u64) as i64)
+            })
+            .ok_or(EmitError::TimeOverflow)?;
+        units.emit::<L>(buffer)
+    }
+}
+
+// the same encoding as `String`, on Unix the raw bytes of `OsStr`,
+// which are not necessarily UTF-8, unless `L::STRICT`
+
+/// `None` if the string is not UTF-8 and it is required.
+fn os_str_bytes<L>(s: &OsStr) -> Option<&[u8]>
+where
+    L: Limit,
+{
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+
+        let bytes = s.as_bytes();
+        if L::STRICT && std::str::from_utf8(bytes).is_err() {
+            None
+        } else {
+            Some(bytes)
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        s.to_str().map(str::as_bytes)
+    }
+}
+
+impl EncodedLen for OsStr {
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        // zero if the string cannot be emitted
+        os_str_bytes::<L>(self).map_or(0, |bytes| bytes.len().encoded_len_with::<L>() + bytes.len())
+    }
+}
+
+impl<W> Emit<W> for OsStr
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        let bytes = os_str_bytes::<L>(self).ok_or(EmitError::NonUtf8Path)?;
+        bytes.len().emit::<L>(buffer)?;
+        buffer.extend(bytes);
+        Ok(())
+    }
+}
+
+impl EncodedLen for OsString {
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        self.as_os_str().encoded_len_with::<L>()
+    }
+}
+
+impl<W> Emit<W> for OsString
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        self.as_os_str().emit::<L>(buffer)
+    }
+}
+
+impl EncodedLen for Path {
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        self.as_os_str().encoded_len_with::<L>()
+    }
+}
+
+impl<W> Emit<W> for Path
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        self.as_os_str().emit::<L>(buffer)
+    }
+}
+
+impl EncodedLen for PathBuf {
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        self.as_os_str().encoded_len_with::<L>()
+    }
+}
+
+impl<W> Emit<W> for PathBuf
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        self.as_os_str().emit::<L>(buffer)
+    }
+}
+
+impl EncodedLen for Box<Path> {
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        self.as_os_str().encoded_len_with::<L>()
+    }
+}
+
+impl<W> Emit<W> for Box<Path>
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        self.as_os_str().emit::<L>(buffer)
     }
 }
@@ -0,0 +1,72 @@
+// Copyright 2022 Vladislav Melnik
+// SPDX-License-Identifier: MIT
+
+use super::{core::Emit, error::EmitError, len::EncodedLen, Limit, LengthPrefix, VarU64, VarI64};
+
+pub(super) fn emit_var_u64<W>(mut value: u64, buffer: &mut W)
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    let mut bytes = [0; 10];
+    let mut len = 0;
+    loop {
+        let group = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes[len] = group;
+            len += 1;
+            break;
+        }
+        bytes[len] = group | 0x80;
+        len += 1;
+    }
+    buffer.extend(&bytes[..len]);
+}
+
+const fn zigzag(v: i64) -> u64 {
+    ((v << 1) ^ (v >> 63)) as u64
+}
+
+impl EncodedLen for VarU64 {
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        LengthPrefix::Var.encoded_len(self.0)
+    }
+}
+
+impl<W> Emit<W> for VarU64
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        emit_var_u64(self.0, buffer);
+        Ok(())
+    }
+}
+
+impl EncodedLen for VarI64 {
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        LengthPrefix::Var.encoded_len(zigzag(self.0))
+    }
+}
+
+impl<W> Emit<W> for VarI64
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        emit_var_u64(zigzag(self.0), buffer);
+        Ok(())
+    }
+}
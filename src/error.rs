@@ -0,0 +1,78 @@
+// Copyright 2022 Vladislav Melnik
+// SPDX-License-Identifier: MIT
+
+use core::fmt;
+use alloc::vec::Vec;
+
+use nom::Needed;
+
+use super::{ParseError, OwnedParseError, EmitError};
+
+/// Either error of the crate, does not borrow the input, usable with `?`.
+#[derive(Debug)]
+pub enum Error {
+    Parse(OwnedParseError),
+    /// The input is too short, see `AbsorbExt::absorb_streaming`.
+    Incomplete(Needed),
+    Emit(EmitError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse(err) => write!(f, "parse: {err}"),
+            Error::Incomplete(Needed::Size(size)) => write!(f, "incomplete, needed {size} bytes"),
+            Error::Incomplete(Needed::Unknown) => write!(f, "incomplete"),
+            Error::Emit(err) => write!(f, "emit: {err}"),
+        }
+    }
+}
+
+#[cfg(any(feature = "std", feature = "codec"))]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Parse(err) => err.source(),
+            Error::Incomplete(_) => None,
+            Error::Emit(err) => err.source(),
+        }
+    }
+}
+
+impl<I> From<ParseError<I>> for Error
+where
+    I: Into<Vec<u8>>,
+{
+    fn from(err: ParseError<I>) -> Self {
+        Error::Parse(err.into_vec())
+    }
+}
+
+impl<I> From<nom::Err<ParseError<I>>> for Error
+where
+    I: Into<Vec<u8>>,
+{
+    fn from(err: nom::Err<ParseError<I>>) -> Self {
+        match err {
+            nom::Err::Incomplete(needed) => Error::Incomplete(needed),
+            nom::Err::Error(err) | nom::Err::Failure(err) => err.into(),
+        }
+    }
+}
+
+impl From<EmitError> for Error {
+    fn from(err: EmitError) -> Self {
+        Error::Emit(err)
+    }
+}
+
+#[cfg(any(feature = "std", feature = "codec"))]
+impl From<Error> for std::io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Parse(err) => err.into(),
+            Error::Incomplete(_) => std::io::Error::new(std::io::ErrorKind::UnexpectedEof, err),
+            Error::Emit(err) => err.into(),
+        }
+    }
+}
@@ -11,18 +11,33 @@ extern crate alloc;
 
 extern crate self as radiation;
 
+use core::{fmt, marker::PhantomData, ops::Add};
+
 #[cfg(feature = "derive")]
-pub use radiation_macros::{Absorb, Emit, Limit};
+pub use radiation_macros::{Absorb, Emit, Limit, IsCanonical};
 
-pub use nom;
+pub use nom::{self, number::Endianness};
 
 mod absorb;
 pub use self::absorb::{
-    Absorb, AbsorbExt, ParseError, ParseErrorKind, Limit, LimitDescriptor, LimitError,
+    Absorb, AbsorbExt, ParseError, OwnedParseError, ParseErrorKind, PathSegment, TagValue, Source,
+    Diagnostic, with_path, Limit, LimitDescriptor, LimitError, LittleEndian, BigEndian, Inherit,
+    LengthPrefix, AsLengthPrefix, WithPrefix, Strict, Canonical, Acquire, SeqCst, Version, SeqView,
+    RestView, ViewIter,
 };
 
 mod emit;
-pub use self::emit::{RadiationBuffer, Emit};
+pub use self::emit::{RadiationBuffer, Emit, CsBuffer, EmitError, EncodedLen, IsCanonical};
+
+/// The items used by the derive macros, not a part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use super::emit::{Counter, size_sum, size_same, is_canonical};
+    pub use super::absorb::absorb_parsed;
+}
+
+mod error;
+pub use self::error::Error;
 
 #[cfg(all(test, feature = "derive"))]
 mod tests;
@@ -33,6 +48,158 @@ pub struct DynSized<T>(pub T);
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub struct Collection<C>(pub C);
 
+/// Like `Collection<C>`, for the container whose order does not matter,
+/// e.g. `Unordered<HashSet<T>>`, in canonical mode the elements are sorted
+/// by their encoding and the duplicates are rejected.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Unordered<C>(pub C);
+
+/// The length prefixed value, `Vec<T>`, `String`, `DynSized<T>`, etc.,
+/// with the length encoded as `P`, e.g. `Prefixed<u8, Vec<u16>>`.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Prefixed<P, T>(pub T, pub PhantomData<P>);
+
+impl<P, T> From<T> for Prefixed<P, T> {
+    fn from(v: T) -> Self {
+        Prefixed(v, PhantomData)
+    }
+}
+
+/// The value encoded in little endian byte order.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Le<T>(pub T);
+
+/// The value encoded in big endian byte order.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Be<T>(pub T);
+
+/// Unsigned LEB128 variable length integer.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VarU64(pub u64);
+
+/// Signed variable length integer, zig-zag encoded into `VarU64`.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VarI64(pub i64);
+
+impl From<u64> for VarU64 {
+    fn from(v: u64) -> Self {
+        VarU64(v)
+    }
+}
+
+impl From<VarU64> for u64 {
+    fn from(v: VarU64) -> Self {
+        v.0
+    }
+}
+
+impl From<i64> for VarI64 {
+    fn from(v: i64) -> Self {
+        VarI64(v)
+    }
+}
+
+impl From<VarI64> for i64 {
+    fn from(v: VarI64) -> Self {
+        v.0
+    }
+}
+
+// allows to use the type as an enum tag, `tag + 1`, wraps around instead of panicking
+impl Add<u64> for VarU64 {
+    type Output = Self;
+
+    fn add(self, rhs: u64) -> Self::Output {
+        VarU64(self.0.wrapping_add(rhs))
+    }
+}
+
+impl Add<i64> for VarI64 {
+    type Output = Self;
+
+    fn add(self, rhs: i64) -> Self::Output {
+        VarI64(self.0.wrapping_add(rhs))
+    }
+}
+
+impl fmt::Display for VarU64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Display for VarI64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// IPv6 socket address with `flowinfo` and `scope_id`, `SocketAddrV6`
+/// or `SocketAddr`, which are dropped by the plain encoding.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WithScope<T>(pub T);
+
+/// `SystemTime` encoded as `i64` number of `P` units since the Unix epoch,
+/// negative before it, the fraction of the unit is truncated towards the past.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnixTimestamp<P = Seconds>(pub std::time::SystemTime, pub PhantomData<P>);
+
+#[cfg(feature = "std")]
+impl<P> From<std::time::SystemTime> for UnixTimestamp<P> {
+    fn from(v: std::time::SystemTime) -> Self {
+        UnixTimestamp(v, PhantomData)
+    }
+}
+
+const NANOS_PER_SECOND: u32 = 1_000_000_000;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// The unit of `UnixTimestamp`, sealed, the number of units per second
+/// divides a billion.
+pub trait Precision: sealed::Sealed {
+    const PER_SECOND: u64;
+}
+
+/// The `i64` of `UnixTimestamp<Seconds>` counts seconds since the epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Seconds;
+
+/// The `i64` of `UnixTimestamp<Millis>` counts milliseconds since the epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Millis;
+
+/// The `i64` of `UnixTimestamp<Micros>` counts microseconds since the epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Micros;
+
+/// The `i64` of `UnixTimestamp<Nanos>` counts nanoseconds since the epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Nanos;
+
+macro_rules! impl_precision {
+    ($($t:ty = $per_second:expr;)*) => {
+        $(
+            impl sealed::Sealed for $t {}
+
+            impl Precision for $t {
+                const PER_SECOND: u64 = $per_second;
+            }
+        )*
+    };
+}
+
+impl_precision!(
+    Seconds = 1;
+    Millis = 1_000;
+    Micros = 1_000_000;
+    Nanos = NANOS_PER_SECOND as u64;
+);
+
 #[cfg(feature = "codec")]
 pub mod codec;
 
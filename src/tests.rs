@@ -1,11 +1,15 @@
 // Copyright 2022 Vladislav Melnik
 // SPDX-License-Identifier: MIT
 
-use alloc::{boxed::Box, vec::Vec};
+use alloc::{boxed::Box, vec::Vec, borrow::Cow};
 
-use super::{AbsorbExt, Absorb, ParseError, ParseErrorKind, Emit, DynSized, Limit};
+use super::{
+    AbsorbExt, Absorb, ParseError, ParseErrorKind, Emit, EmitError, EncodedLen, DynSized, Limit,
+    CsBuffer, Le, Be, Prefixed, Collection, Unordered, VarU64, VarI64, IsCanonical, Error, SeqView,
+    RestView, Acquire, WithScope, UnixTimestamp, Seconds, Millis, Nanos, Version,
+};
 
-#[derive(Debug, PartialEq, Eq, Absorb, Emit)]
+#[derive(Debug, PartialEq, Eq, Absorb, Emit, IsCanonical)]
 struct SomeStruct {
     pub a: u8,
     pub b: u16,
@@ -15,7 +19,7 @@ struct SomeStruct {
 #[test]
 fn trivial_struct() {
     let foo = SomeStruct::absorb_ext(b"\x12\x23\x34\x45\x56\x67\x78").unwrap();
-    assert_eq!(foo.chain(vec![]), b"\x12\x23\x34\x45\x56\x67\x78");
+    assert_eq!(foo.chain(vec![]).unwrap(), b"\x12\x23\x34\x45\x56\x67\x78");
     assert_eq!(
         foo,
         SomeStruct {
@@ -44,22 +48,57 @@ enum SomeEnum {
 }
 
 // custom parser, parse u8 and square the result
-fn absorb<'pa>(input: &'pa [u8]) -> nom::IResult<&'pa [u8], u16, ParseError<&'pa [u8]>> {
+fn absorb(input: &[u8]) -> nom::IResult<&[u8], u16, ParseError<&[u8]>> {
     crate::nom::combinator::map(u8::absorb::<()>, |a| a as u16 * a as u16)(input)
 }
 
-fn emit<W>(value: &u16, buffer: &mut W)
+#[derive(Debug, Absorb)]
+struct Number {
+    #[custom_absorb(absorb_number)]
+    value: u16,
+}
+
+// custom parser, keeps the error of `FromStr` like `#[as_str]` does
+fn absorb_number(input: &[u8]) -> nom::IResult<&[u8], u16, ParseError<&[u8]>> {
+    let (rest, s) = <&str>::absorb::<()>(input)?;
+    match s.parse() {
+        Ok(value) => Ok((rest, value)),
+        Err(err) => {
+            let kind = nom::error::ErrorKind::MapRes;
+            Err(nom::Err::Error(ParseError::custom(input, kind, err)))
+        }
+    }
+}
+
+fn emit<W>(value: &u16, buffer: &mut W) -> Result<(), EmitError>
 where
     W: for<'a> Extend<&'a u8>,
 {
     let a = (*value as f32).sqrt() as u8;
-    a.emit(buffer);
+    a.emit::<()>(buffer)
+}
+
+fn emit_square<W>(value: &u16, buffer: &mut W) -> Result<(), EmitError>
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    let a = (*value as f32).sqrt() as u8;
+    if a as u16 * a as u16 != *value {
+        return Err(EmitError::custom("not a square"));
+    }
+    a.emit::<()>(buffer)
+}
+
+#[derive(Debug, Emit)]
+struct Square {
+    #[custom_emit(emit_square)]
+    value: u16,
 }
 
 #[test]
 fn trivial_enum() {
     let a = SomeEnum::absorb_ext(b"\x01\xcc\xdd\x12").unwrap();
-    assert_eq!(a.chain(vec![]), b"\x01\xcc\xdd\x12");
+    assert_eq!(a.chain(vec![]).unwrap(), b"\x01\xcc\xdd\x12");
     assert_eq!(
         a,
         SomeEnum::A {
@@ -70,11 +109,11 @@ fn trivial_enum() {
     );
 
     let b = SomeEnum::absorb_ext(b"\x02\x00\x00\x00\x0512345").unwrap();
-    assert_eq!(b.chain(vec![]), b"\x02\x00\x00\x00\x0512345");
+    assert_eq!(b.chain(vec![]).unwrap(), b"\x02\x00\x00\x00\x0512345");
     assert_eq!(b, SomeEnum::B(12345));
 
     let c = SomeEnum::absorb_ext(b"\x03\x12\x34\xab\xcd").unwrap();
-    assert_eq!(c.chain(vec![]), b"\x03\x12\x34\xab\xcd");
+    assert_eq!(c.chain(vec![]).unwrap(), b"\x03\x12\x34\xab\xcd");
     assert_eq!(c, SomeEnum::C(0x1234abcd));
 
     let err = SomeEnum::absorb_ext(b"\x04").unwrap_err();
@@ -118,6 +157,40 @@ struct LimitedInner {
     two: Box<[u16]>,
 }
 
+// the sender refuses to emit the value which violates the limit,
+// so emit the fields bypassing the limit to test the receiver
+fn emit_unchecked(limited: &Limited, hint: &str) -> Vec<u8> {
+    let err = limited.chain(vec![]).unwrap_err();
+    assert!(matches!(err, EmitError::Limit(_, h) if h == hint));
+
+    let bytes = limited.small.chain(vec![]).unwrap();
+    limited.big.chain(bytes).unwrap()
+}
+
+#[test]
+fn test_limits_emit() {
+    let limited = Limited {
+        small: 321,
+        big: DynSized(LimitedInner {
+            one: vec![0x12345; 4],
+            two: Box::new([0; 4]),
+        }),
+    };
+    let bytes = limited.chain(vec![]).unwrap();
+    <Limited as AbsorbExt>::absorb_ext(&bytes).unwrap();
+
+    let limited = Limited {
+        small: 321,
+        big: DynSized(LimitedInner {
+            // this is 7 * 4 == 28 bytes long, which is over limit 24
+            one: vec![0x12345; 7],
+            two: Box::new([0; 1]),
+        }),
+    };
+    let err = limited.chain(vec![]).unwrap_err();
+    assert!(matches!(err, EmitError::Limit(_, hint) if hint == stringify!(LimitOne)));
+}
+
 #[test]
 fn test_limits_fail_0() {
     let limited = Limited {
@@ -128,7 +201,7 @@ fn test_limits_fail_0() {
             two: Box::new([0; 5]),
         }),
     };
-    let bytes = limited.chain(vec![]);
+    let bytes = emit_unchecked(&limited, stringify!(LimitBig));
     let err = <Limited as AbsorbExt>::absorb_ext(&bytes).unwrap_err();
     if let nom::Err::Error(err) = &err {
         if let ParseErrorKind::Limit(_, hint) = &err.kind {
@@ -150,7 +223,7 @@ fn test_limits_fail_1() {
             two: Box::new([0; 1]),
         }),
     };
-    let bytes = limited.chain(vec![]);
+    let bytes = emit_unchecked(&limited, stringify!(LimitOne));
     let err = <Limited as AbsorbExt>::absorb_ext(&bytes).unwrap_err();
     if let nom::Err::Error(err) = &err {
         if let ParseErrorKind::Limit(_, hint) = &err.kind {
@@ -172,7 +245,7 @@ fn test_limits_fail_2() {
             two: Box::new([0; 1]),
         }),
     };
-    let bytes = limited.chain(vec![]);
+    let bytes = emit_unchecked(&limited, stringify!(LimitOne));
     let err = <Limited as AbsorbExt>::absorb_ext(&bytes).unwrap_err();
     if let nom::Err::Error(err) = &err {
         if let ParseErrorKind::Limit(_, hint) = &err.kind {
@@ -194,7 +267,7 @@ fn test_limits_fail_3() {
             two: Box::new([0; 5]),
         }),
     };
-    let bytes = limited.chain(vec![]);
+    let bytes = emit_unchecked(&limited, stringify!(LimitTwo));
     let err = <Limited as AbsorbExt>::absorb_ext(&bytes).unwrap_err();
     if let nom::Err::Error(err) = &err {
         if let ParseErrorKind::Limit(_, hint) = &err.kind {
@@ -205,3 +278,1427 @@ fn test_limits_fail_3() {
     }
     panic!("wrong error {err}");
 }
+
+#[derive(Debug, PartialEq, Eq, Absorb, Emit)]
+struct Framed {
+    id: u16,
+    payload: Vec<u32>,
+    inner: DynSized<SomeStruct>,
+}
+
+#[test]
+fn streaming() {
+    let framed = Framed {
+        id: 0x1234,
+        payload: vec![1, 2],
+        inner: DynSized(SomeStruct { a: 1, b: 2, c: 3 }),
+    };
+    let bytes = framed.chain(vec![]).unwrap();
+    assert_eq!(bytes.len(), 2 + 4 + 8 + 4 + 7);
+
+    // each prefix reports how many bytes are needed to make progress
+    let needed = |len: usize| match Framed::absorb_streaming(&bytes[..len]) {
+        Err(nom::Err::Incomplete(nom::Needed::Size(size))) => size.get(),
+        other => panic!("unexpected {other:?}"),
+    };
+    assert_eq!(needed(0), 2);
+    assert_eq!(needed(1), 1);
+    assert_eq!(needed(3), 3);
+    assert_eq!(needed(6), 8);
+    assert_eq!(needed(10), 4);
+    assert_eq!(needed(16), 2);
+    assert_eq!(needed(18), 7);
+
+    let (value, len) = Framed::absorb_streaming(&bytes).unwrap();
+    assert_eq!(value, framed);
+    assert_eq!(len, bytes.len());
+
+    // complete mode reports the lack of bytes as an error
+    let err = Framed::absorb_ext(&bytes[..10]).unwrap_err();
+    assert!(matches!(err, nom::Err::Error(ref err) if err.kind.is_eof()));
+}
+
+#[test]
+fn streaming_corrupted() {
+    // the inner structure claims 6 bytes, but `SomeStruct` needs 7
+    let bytes = b"\x12\x34\x00\x00\x00\x00\x00\x00\x00\x06\x01\x00\x02\x00\x00\x00";
+    let err = Framed::absorb_streaming(bytes).unwrap_err();
+    assert!(matches!(err, nom::Err::Error(ref err) if err.kind.is_eof()));
+}
+
+#[test]
+fn emit_errors() {
+    let foo = SomeStruct {
+        a: 0x12,
+        b: 0x2334,
+        c: 0x45566778,
+    };
+    let buffer = foo.chain(CsBuffer::<8>::new()).unwrap();
+    assert_eq!(buffer.as_slice().unwrap(), b"\x12\x23\x34\x45\x56\x67\x78");
+    assert!(matches!(
+        foo.chain(CsBuffer::<4>::new()),
+        Err(EmitError::BufferFull {
+            capacity: 4,
+            required: 7
+        })
+    ));
+    let mut buffer = CsBuffer::<4>::new();
+    foo.emit::<()>(&mut buffer).unwrap();
+    assert!(matches!(
+        buffer.as_slice(),
+        Err(EmitError::BufferFull {
+            capacity: 4,
+            required: 7
+        })
+    ));
+
+    assert!(matches!(
+        Square { value: 3 }.chain(vec![]),
+        Err(EmitError::Custom(_))
+    ));
+}
+
+#[cfg(feature = "codec")]
+#[test]
+fn codec_emit_error() {
+    use tokio_util::codec::Encoder;
+
+    let mut dst = bytes::BytesMut::from(&b"ab"[..]);
+    let mut codec = super::codec::Codec::default();
+    assert!(codec
+        .encode((0x1234u16, Square { value: 3 }), &mut dst)
+        .is_err());
+    assert_eq!(&dst[..], b"ab");
+    codec
+        .encode((0x1234u16, Square { value: 4 }), &mut dst)
+        .unwrap();
+    assert_eq!(&dst[..], b"ab\x12\x34\x02");
+}
+
+#[derive(Absorb, Emit)]
+#[tag(u8)]
+enum Fixed {
+    A(u16, u16),
+    B([u8; 4]),
+    C { a: bool, b: i8, c: u16 },
+}
+
+#[test]
+fn encoded_len() {
+    assert_eq!(SomeStruct::SIZE, Some(7));
+    assert_eq!(Fixed::SIZE, Some(5));
+    assert_eq!(SomeEnum::SIZE, None);
+    assert_eq!(Framed::SIZE, None);
+
+    let framed = Framed {
+        id: 0x1234,
+        payload: vec![1, 2, 3],
+        inner: DynSized(SomeStruct { a: 1, b: 2, c: 3 }),
+    };
+    assert_eq!(framed.encoded_len(), framed.chain(vec![]).unwrap().len());
+
+    let a = SomeEnum::A {
+        one: 0xcc,
+        two: 0xdd,
+        three: 0x12 * 0x12,
+    };
+    assert_eq!(a.encoded_len(), 4);
+    assert_eq!(SomeEnum::B(12345).encoded_len(), 10);
+}
+
+#[derive(Debug, PartialEq, Eq, Absorb, Emit)]
+#[radiation(endian = "little")]
+struct LittleEndianStruct {
+    a: u16,
+    // the length prefix and the elements are little endian as well
+    b: Vec<u16>,
+    // the nested types inherit the byte order
+    c: SomeStruct,
+    #[limit(LimitTwo)]
+    d: Box<[u16]>,
+}
+
+#[derive(Debug, PartialEq, Eq, Absorb, Emit)]
+enum MixedEndian {
+    A(Be<u32>, Le<u32>),
+    B { inner: Le<LittleEndianStruct> },
+}
+
+#[test]
+fn endianness() {
+    let value = LittleEndianStruct {
+        a: 0x1234,
+        b: vec![0x5678],
+        c: SomeStruct {
+            a: 0x12,
+            b: 0x2334,
+            c: 0x45566778,
+        },
+        d: Box::new([0xabcd]),
+    };
+    let bytes = b"\x34\x12\x02\x00\x00\x00\x78\x56\x12\x34\x23\x78\x67\x56\x45\xcd\xab";
+    assert_eq!(value.chain(vec![]).unwrap(), bytes);
+    assert_eq!(LittleEndianStruct::absorb_ext(bytes).unwrap(), value);
+
+    let a = MixedEndian::A(Be(0x12345678), Le(0x12345678));
+    let bytes = b"\x00\x00\x12\x34\x56\x78\x78\x56\x34\x12";
+    assert_eq!(a.chain(vec![]).unwrap(), bytes);
+    assert_eq!(MixedEndian::absorb_ext(bytes).unwrap(), a);
+
+    // the enum tag follows the byte order
+    let bytes = Le(MixedEndian::A(Be(1), Le(2))).chain(vec![]).unwrap();
+    assert_eq!(&bytes[..2], b"\x00\x00");
+    let bytes = Le(MixedEndian::B { inner: Le(value) })
+        .chain(vec![])
+        .unwrap();
+    assert_eq!(&bytes[..4], b"\x01\x00\x34\x12");
+}
+
+#[derive(Debug, PartialEq, Eq, Absorb, Emit)]
+struct ShortPrefixes {
+    a: Prefixed<u8, Vec<u16>>,
+    #[radiation(prefix = u16)]
+    b: alloc::string::String,
+    #[limit(LimitTwo)]
+    #[radiation(prefix = u64)]
+    c: Vec<u8>,
+}
+
+#[test]
+fn length_prefix() {
+    let value = ShortPrefixes {
+        a: vec![0x1234].into(),
+        b: "abc".into(),
+        c: vec![1, 2],
+    };
+    let bytes = b"\x02\x12\x34\x00\x03abc\x00\x00\x00\x00\x00\x00\x00\x02\x01\x02";
+    assert_eq!(value.encoded_len(), bytes.len());
+    assert_eq!(value.chain(vec![]).unwrap(), bytes);
+    assert_eq!(ShortPrefixes::absorb_ext(bytes).unwrap(), value);
+
+    // the limit is checked on the decoded length
+    let bytes = b"\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x09123456789";
+    match ShortPrefixes::absorb_ext(bytes) {
+        Err(nom::Err::Error(ParseError {
+            kind: ParseErrorKind::Limit(_, hint),
+            ..
+        })) => assert_eq!(hint, stringify!(LimitTwo)),
+        r => panic!("unexpected {r:?}"),
+    }
+
+    // the length does not fit in the prefix
+    let value = Prefixed::<u8, Vec<u8>>::from(vec![0; 0x100]);
+    match value.chain(vec![]) {
+        Err(EmitError::LengthOverflow { length, maximum }) => {
+            assert_eq!((length, maximum), (0x100, 0xff))
+        }
+        r => panic!("unexpected {r:?}"),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Absorb, Emit)]
+#[tag(VarU64)]
+enum VarTagged {
+    A(VarI64),
+    #[tag(VarU64(300))]
+    B(Prefixed<VarU64, Vec<u8>>),
+    C {
+        #[radiation(prefix = VarU64)]
+        s: alloc::string::String,
+    },
+}
+
+#[test]
+fn varint() {
+    let cases: &[(u64, &[u8])] = &[
+        (0, b"\x00"),
+        (1, b"\x01"),
+        (127, b"\x7f"),
+        (128, b"\x80\x01"),
+        (300, b"\xac\x02"),
+        (u64::MAX, b"\xff\xff\xff\xff\xff\xff\xff\xff\xff\x01"),
+    ];
+    for (value, bytes) in cases {
+        assert_eq!(VarU64(*value).chain(vec![]).unwrap(), *bytes);
+        assert_eq!(VarU64(*value).encoded_len(), bytes.len());
+        assert_eq!(VarU64::absorb_ext(bytes).unwrap(), VarU64(*value));
+    }
+    for value in [0, -1, 1, -64, 64, i64::MIN, i64::MAX] {
+        let bytes = VarI64(value).chain(vec![]).unwrap();
+        assert_eq!(VarI64(value).encoded_len(), bytes.len());
+        assert_eq!(VarI64::absorb_ext(&bytes).unwrap(), VarI64(value));
+    }
+    assert_eq!(VarI64(-1).chain(vec![]).unwrap(), b"\x01");
+    assert_eq!(VarI64(1).chain(vec![]).unwrap(), b"\x02");
+
+    match VarU64::absorb_ext(b"\x80\x00") {
+        Err(nom::Err::Error(ParseError {
+            kind: ParseErrorKind::Overlong,
+            ..
+        })) => (),
+        r => panic!("unexpected {r:?}"),
+    }
+    match VarU64::absorb_ext(b"\xff\xff\xff\xff\xff\xff\xff\xff\xff\x02") {
+        Err(nom::Err::Error(ParseError {
+            kind: ParseErrorKind::Nom(nom::error::ErrorKind::TooLarge),
+            ..
+        })) => (),
+        r => panic!("unexpected {r:?}"),
+    }
+    assert!(matches!(
+        VarU64::absorb_streaming(b"\x80\x80"),
+        Err(nom::Err::Incomplete(_))
+    ));
+
+    assert_eq!(VarU64(u64::MAX) + 1, VarU64(0));
+    assert_eq!(VarI64(i64::MAX) + 1, VarI64(i64::MIN));
+
+    let value = VarTagged::B(vec![1, 2].into());
+    let bytes = b"\xac\x02\x02\x01\x02";
+    assert_eq!(value.encoded_len(), bytes.len());
+    assert_eq!(value.chain(vec![]).unwrap(), bytes);
+    assert_eq!(VarTagged::absorb_ext(bytes).unwrap(), value);
+
+    let value = VarTagged::C { s: "abc".into() };
+    let bytes = b"\xad\x02\x03abc";
+    assert_eq!(value.chain(vec![]).unwrap(), bytes);
+    assert_eq!(VarTagged::absorb_ext(bytes).unwrap(), value);
+
+    let value = VarTagged::A(VarI64(-2));
+    assert_eq!(value.chain(vec![]).unwrap(), b"\x00\x03");
+}
+
+#[test]
+fn tuples_and_arrays() {
+    type Big = (
+        u8,
+        u16,
+        u32,
+        u64,
+        i8,
+        i16,
+        i32,
+        i64,
+        bool,
+        [u8; 2],
+        Vec<u8>,
+        (u8,),
+    );
+    let value: Big = (1, 2, 3, 4, -1, -2, -3, -4, true, [5, 6], vec![7], (8,));
+    let bytes = value.chain(vec![]).unwrap();
+    assert_eq!(bytes.len(), value.encoded_len());
+    assert_eq!(Big::absorb_ext(&bytes).unwrap(), value);
+
+    let value = [[0x1234_u16, 0x5678], [0x9abc, 0xdef0]];
+    assert_eq!(<[[u16; 2]; 2]>::SIZE, Some(8));
+    let bytes = value.chain(vec![]).unwrap();
+    assert_eq!(bytes, b"\x12\x34\x56\x78\x9a\xbc\xde\xf0");
+    assert_eq!(<[[u16; 2]; 2]>::absorb_ext(&bytes).unwrap(), value);
+    assert!(<[[u16; 2]; 2]>::absorb_ext(&bytes[..7]).is_err());
+    assert!(matches!(
+        <[u8; 4]>::absorb_streaming(b"\x01\x02"),
+        Err(nom::Err::Incomplete(_))
+    ));
+    assert_eq!(
+        [1_u8, 2, 3]
+            .chain(CsBuffer::<3>::new())
+            .unwrap()
+            .as_slice()
+            .unwrap(),
+        [1, 2, 3]
+    );
+
+    let addr = "127.0.0.1:8080".parse::<std::net::SocketAddr>().unwrap();
+    let value = [addr, "[::1]:443".parse().unwrap()];
+    let bytes = value.chain(vec![]).unwrap();
+    assert_eq!(
+        <[std::net::SocketAddr; 2]>::absorb_ext(&bytes).unwrap(),
+        value
+    );
+
+    // the second element is limited by the next limit
+    let value = (vec![0_u8; 16], vec![0_u8; 9]);
+    let mut bytes = vec![];
+    match value.emit::<LimitOne>(&mut bytes) {
+        Err(EmitError::Limit(_, hint)) => assert_eq!(hint, stringify!(LimitTwo)),
+        r => panic!("unexpected {r:?}"),
+    }
+    let bytes = (vec![0_u8; 16], vec![0_u8; 8]).chain(vec![]).unwrap();
+    assert!(<(Vec<u8>, Vec<u8>)>::absorb::<LimitOne>(&bytes).is_ok());
+    let bytes = value.chain(vec![]).unwrap();
+    match <(Vec<u8>, Vec<u8>)>::absorb::<LimitOne>(&bytes) {
+        Err(nom::Err::Error(ParseError {
+            kind: ParseErrorKind::Limit(_, hint),
+            ..
+        })) => assert_eq!(hint, stringify!(LimitTwo)),
+        r => panic!("unexpected {r:?}"),
+    }
+}
+
+#[test]
+fn wide_integers_char_non_zero() {
+    use core::num::{NonZeroU16, NonZeroI128};
+
+    let value = (
+        u128::MAX - 1,
+        -2_i128,
+        'ф',
+        NonZeroU16::new(0x1234).unwrap(),
+    );
+    let bytes = value.chain(vec![]).unwrap();
+    assert_eq!(bytes.len(), 16 + 16 + 4 + 2);
+    assert_eq!(<(u128, i128, char, NonZeroU16)>::SIZE, Some(bytes.len()));
+    assert_eq!(&bytes[32..], b"\x00\x00\x04\x44\x12\x34");
+    assert_eq!(
+        <(u128, i128, char, NonZeroU16)>::absorb_ext(&bytes).unwrap(),
+        value
+    );
+
+    match char::absorb_ext(b"\x00\x00\xd8\x00") {
+        Err(nom::Err::Error(ParseError {
+            kind: ParseErrorKind::InvalidChar(0xd800),
+            ..
+        })) => (),
+        r => panic!("unexpected {r:?}"),
+    }
+    match NonZeroI128::absorb_ext(&[0; 16]) {
+        Err(nom::Err::Error(ParseError {
+            kind: ParseErrorKind::Zero(hint),
+            ..
+        })) => assert_eq!(hint, stringify!(NonZeroI128)),
+        r => panic!("unexpected {r:?}"),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Absorb, Emit)]
+struct Collections {
+    map: alloc::collections::BTreeMap<u8, alloc::string::String>,
+    #[radiation(strict)]
+    set: alloc::collections::BTreeSet<u16>,
+    hash: std::collections::HashMap<u32, bool>,
+    #[radiation(strict)]
+    hash_set: std::collections::HashSet<u8>,
+    deque: alloc::collections::VecDeque<u8>,
+}
+
+#[test]
+fn collections() {
+    let value = Collections {
+        map: [(2, "b".into()), (1, "a".into())].into_iter().collect(),
+        set: [3, 1, 2].into_iter().collect(),
+        hash: [(7, true)].into_iter().collect(),
+        hash_set: [9].into_iter().collect(),
+        deque: [4, 5].into_iter().collect(),
+    };
+    let bytes = value.chain(vec![]).unwrap();
+    assert_eq!(bytes.len(), value.encoded_len());
+    assert_eq!(
+        &bytes[..24],
+        b"\x00\x00\x00\x0c\x01\x00\x00\x00\x01a\x02\x00\x00\x00\x01b\
+          \x00\x00\x00\x06\x00\x01\x00\x02",
+    );
+    assert_eq!(Collections::absorb_ext(&bytes).unwrap(), value);
+
+    // the same wire format as `Vec`, not strict by default
+    let bytes = vec![(2_u8, 0_u8), (1, 0), (1, 1)].chain(vec![]).unwrap();
+    let map = <alloc::collections::BTreeMap<u8, u8>>::absorb_ext(&bytes).unwrap();
+    assert_eq!(map.into_iter().collect::<Vec<_>>(), [(1, 1), (2, 0)]);
+    let err = |r| match r {
+        Err(nom::Err::Error(ParseError { kind, .. })) => kind,
+        _ => panic!("unexpected"),
+    };
+    let r = <alloc::collections::BTreeMap<u8, u8>>::absorb::<crate::Strict>(&bytes);
+    assert!(matches!(err(r.map(|_| ())), ParseErrorKind::UnsortedKey));
+
+    let bytes = vec![1_u8, 1].chain(vec![]).unwrap();
+    let r = <alloc::collections::BTreeSet<u8>>::absorb::<crate::Strict>(&bytes);
+    assert!(matches!(err(r.map(|_| ())), ParseErrorKind::DuplicateKey));
+    let r = <std::collections::HashSet<u8>>::absorb::<crate::Strict>(&bytes);
+    assert!(matches!(err(r.map(|_| ())), ParseErrorKind::DuplicateKey));
+    assert_eq!(
+        <std::collections::HashSet<u8>>::absorb_ext(&bytes)
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[derive(Debug, Absorb, Emit, IsCanonical)]
+struct Signed {
+    value: f64,
+    tags: std::collections::HashSet<u16>,
+    attributes: std::collections::HashMap<u8, Vec<u8>>,
+}
+
+#[derive(Debug, PartialEq, Absorb, Emit, IsCanonical)]
+struct Custom {
+    #[custom_absorb(absorb)]
+    #[custom_emit(emit_twice)]
+    value: u16,
+}
+
+// not a valid encoding, absorb takes one byte
+fn emit_twice<W>(value: &u16, buffer: &mut W) -> Result<(), EmitError>
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    emit(value, buffer)?;
+    emit(value, buffer)
+}
+
+#[test]
+fn canonical() {
+    let nan = f64::from_bits(f64::NAN.to_bits() | 1);
+    let value = Signed {
+        value: nan,
+        tags: (0..64).collect(),
+        attributes: (0..64).map(|i| (i, vec![i])).collect(),
+    };
+    assert!(!value.is_canonical());
+    let bytes = value.chain_canonical(vec![]).unwrap();
+    assert_eq!(&bytes[..8], f64::NAN.to_be_bytes());
+    assert_eq!(&bytes[12..16], b"\x00\x00\x00\x01");
+    let value = Signed::absorb_canonical(&bytes).unwrap();
+    assert_eq!(value.chain_canonical(vec![]).unwrap(), bytes);
+
+    // the regular encoding is accepted, but not in canonical mode
+    let regular = Signed {
+        value: nan,
+        tags: Default::default(),
+        attributes: Default::default(),
+    };
+    let bytes = regular.chain(vec![]).unwrap();
+    assert!(Signed::absorb_ext(&bytes).is_ok());
+    match Signed::absorb_canonical(&bytes) {
+        Err(nom::Err::Error(ParseError {
+            kind: ParseErrorKind::NotCanonical(_),
+            ..
+        })) => (),
+        r => panic!("unexpected {r:?}"),
+    }
+    let bytes = vec![2_u16, 1].chain(vec![]).unwrap();
+    match <std::collections::HashSet<u16>>::absorb_canonical(&bytes) {
+        Err(nom::Err::Error(ParseError {
+            kind: ParseErrorKind::UnsortedKey,
+            ..
+        })) => (),
+        r => panic!("unexpected {r:?}"),
+    }
+
+    let bytes = Collection(vec![2_u8, 1, 1])
+        .chain_canonical(vec![])
+        .unwrap();
+    assert_eq!(bytes, [2, 1, 1]);
+    let value = <Collection<Vec<u8>>>::absorb_canonical(&bytes).unwrap();
+    assert_eq!(value.0, [2, 1, 1]);
+    let set = [2_u8, 1]
+        .into_iter()
+        .collect::<std::collections::HashSet<_>>();
+    let bytes = Unordered(set).chain_canonical(vec![]).unwrap();
+    assert_eq!(bytes, [1, 2]);
+    match <Unordered<std::collections::HashSet<u8>>>::absorb_canonical(&[2, 1]) {
+        Err(nom::Err::Error(ParseError {
+            kind: ParseErrorKind::UnsortedKey,
+            ..
+        })) => (),
+        r => panic!("unexpected {r:?}"),
+    }
+
+    assert!(SomeStruct { a: 1, b: 2, c: 3 }.is_canonical());
+    assert!(!Custom { value: 4 }.is_canonical());
+    assert!(VarI64(-5).is_canonical());
+}
+
+#[test]
+fn collection_of_any_container() {
+    let list = [3_u8, 1, 2]
+        .into_iter()
+        .collect::<std::collections::LinkedList<_>>();
+    let bytes = Collection(list.clone()).chain(vec![]).unwrap();
+    assert_eq!(bytes, [3, 1, 2]);
+    let value = <Collection<std::collections::LinkedList<u8>>>::absorb_ext(&bytes).unwrap();
+    assert_eq!(value.0, list);
+    let value = <Collection<std::collections::LinkedList<u8>>>::absorb_canonical(&bytes).unwrap();
+    assert_eq!(value.0, list);
+}
+
+#[derive(Debug, Absorb, Emit)]
+struct Nested {
+    id: u8,
+    items: Vec<SomeEnum>,
+}
+
+#[test]
+fn error_path() {
+    let limited = Limited {
+        small: 321,
+        big: DynSized(LimitedInner {
+            one: vec![0x12345; 7],
+            two: Box::new([0; 1]),
+        }),
+    };
+    let bytes = emit_unchecked(&limited, stringify!(LimitOne));
+    let err = match Limited::absorb_ext(&bytes) {
+        Err(nom::Err::Error(err)) => err,
+        r => panic!("unexpected {r:?}"),
+    };
+    assert_eq!(err.path_string(), "Limited.big.one");
+    assert_eq!(err.offset(&bytes), Some(10));
+    assert_eq!(
+        err.report(&bytes),
+        "Limited.big.one: kind: LimitOne, falls outside the allowed maximum, 28 > 24 at offset 10",
+    );
+
+    let bytes = b"\x01\x00\x00\x00\x0b\x03\x00\x00\x00\x01\x02\x00\x00\x00\x01x";
+    let err = match Nested::absorb_ext(bytes) {
+        Err(nom::Err::Error(err)) => err,
+        r => panic!("unexpected {r:?}"),
+    };
+    assert_eq!(err.path_string(), "Nested.items[1]::B.0");
+    assert_eq!(err.offset(bytes), Some(11));
+}
+
+#[test]
+fn error_kinds() {
+    fn kind<T>(r: Result<T, nom::Err<ParseError<&[u8]>>>) -> ParseErrorKind {
+        match r {
+            Err(nom::Err::Error(err)) => err.kind,
+            _ => panic!("expected error"),
+        }
+    }
+
+    assert!(matches!(
+        kind(bool::absorb_ext(b"\x01")),
+        ParseErrorKind::InvalidBool(1)
+    ));
+    assert!(matches!(
+        kind(<Option<u8>>::absorb_ext(b"\x01\x00")),
+        ParseErrorKind::InvalidDiscriminant {
+            value: 1,
+            hint: "Option"
+        }
+    ));
+    assert!(matches!(
+        kind(<Result<u8, u8>>::absorb_ext(b"\x00\x00")),
+        ParseErrorKind::InvalidDiscriminant {
+            value: 0,
+            hint: "Result"
+        }
+    ));
+    let bytes = <Result<u8, u8>>::Err(7).chain(vec![]).unwrap();
+    assert_eq!(bytes, [0xfe, 7]);
+    assert_eq!(<Result<u8, u8>>::absorb_ext(&bytes).unwrap(), Err(7));
+    let bytes = <Result<u8, u8>>::Ok(7).chain(vec![]).unwrap();
+    assert_eq!(<Result<u8, u8>>::absorb_ext(&bytes).unwrap(), Ok(7));
+    assert!(matches!(
+        kind(<alloc::string::String>::absorb_ext(
+            b"\x00\x00\x00\x03ab\xff"
+        )),
+        ParseErrorKind::InvalidUtf8 { valid_up_to: 2 }
+    ));
+    assert!(matches!(
+        kind(<Vec<()>>::absorb_ext(b"\x00\x00\x00\x01\x00")),
+        ParseErrorKind::ZeroSizedLoop
+    ));
+
+    match kind(SomeEnum::absorb_ext(b"\x04")) {
+        ParseErrorKind::UnknownTag { tag, hint } => {
+            assert_eq!(tag.downcast_ref::<u8>(), Some(&4));
+            assert_eq!(hint, stringify!(SomeEnum));
+        }
+        kind => panic!("unexpected {kind}"),
+    }
+
+    // the error of `FromStr` is kept
+    let as_str = kind(SomeEnum::absorb_ext(b"\x02\x00\x00\x00\x01x"));
+    let source = as_str.source().expect("custom error");
+    assert_eq!(source.message(), "invalid digit found in string");
+    let error = source.error().expect("kept error");
+    assert!(error.downcast_ref::<core::num::ParseIntError>().is_some());
+
+    // the error itself is kept by `ParseError::custom`
+    assert_eq!(Number::absorb_ext(b"\x00\x00\x00\x0242").unwrap().value, 42);
+    let kind = kind(Number::absorb_ext(b"\x00\x00\x00\x01x"));
+    let source = kind.source().expect("custom error");
+    let error = source.error().expect("kept error");
+    assert!(error.downcast_ref::<core::num::ParseIntError>().is_some());
+}
+
+#[test]
+fn error_conversions() {
+    use std::{error::Error as _, io};
+
+    fn parse(bytes: &[u8]) -> Result<SomeStruct, Error> {
+        Ok(SomeStruct::absorb_ext(bytes)?)
+    }
+
+    fn roundtrip(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(parse(bytes)?.chain(vec![])?)
+    }
+
+    let bytes = b"\x12\x23\x34\x45\x56\x67\x78";
+    assert_eq!(roundtrip(bytes).unwrap(), bytes);
+
+    let err = Error::from(SomeStruct::absorb::<()>(b"\x12\x23").unwrap_err());
+    assert!(matches!(err, Error::Incomplete(nom::Needed::Size(_))));
+    let err = io::Error::from(err);
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+    // the source of the custom parser is reachable
+    let err = Number::absorb_ext(b"\x00\x00\x00\x01x")
+        .map_err(Error::from)
+        .unwrap_err();
+    let source = err.source().expect("custom error");
+    assert!(source.downcast_ref::<core::num::ParseIntError>().is_some());
+
+    let err = bool::absorb_ext(b"\x01").map_err(Error::from).unwrap_err();
+    assert!(err.to_string().contains("invalid bool"));
+    let err = io::Error::from(err);
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let err = EmitError::custom("rejected");
+    assert_eq!(io::Error::from(err).kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn diagnostic() {
+    let limited = Limited {
+        small: 321,
+        big: DynSized(LimitedInner {
+            one: vec![0x12345; 7],
+            two: Box::new([0; 1]),
+        }),
+    };
+    let bytes = emit_unchecked(&limited, stringify!(LimitOne));
+    let err = match Limited::absorb_ext(&bytes) {
+        Err(nom::Err::Error(err)) => err,
+        r => panic!("unexpected {r:?}"),
+    };
+    assert_eq!(
+        err.diagnostic(&bytes).context(1).to_string(),
+        "\
+error: Limited.big.one: LimitOne, falls outside the allowed maximum, 28 > 24
+  at offset 10 (0xa) of 40 bytes
+00000000  01 41 00 00 00 22 00 00  00 1c[00]01 23 45 00 01  |.A...\"......#E..|
+00000010  23 45 00 01 23 45 00 01  23 45 00 01 23 45 00 01  |#E..#E..#E..#E..|
+",
+    );
+    // the end of the buffer
+    assert!(err
+        .diagnostic(&bytes[..10])
+        .to_string()
+        .ends_with("00 1c[  ]                |.A...\"....|\n"));
+    // the input is not in the buffer
+    let other = bytes.clone();
+    assert_eq!(err.diagnostic(&other).to_string().lines().count(), 2);
+
+    // the chain of subsequent errors
+    use nom::error::{ErrorKind, ParseError as _};
+    let err = ParseError::append(&bytes[..], ErrorKind::Verify, err);
+    let text = err.diagnostic(&bytes).to_string();
+    let mut lines = text.lines();
+    assert_eq!(lines.next(), Some("error: Verify"));
+    assert_eq!(lines.next(), Some("  at offset 0 (0x0) of 40 bytes"));
+    assert_eq!(
+        lines.nth(3),
+        Some("caused by: Limited.big.one: LimitOne, falls outside the allowed maximum, 28 > 24"),
+    );
+    assert_eq!(lines.next(), Some("  at offset 10 (0xa) of 40 bytes"));
+    assert_eq!(lines.next(), None);
+}
+
+#[test]
+fn views() {
+    let items = vec![
+        SomeStruct { a: 1, b: 2, c: 3 },
+        SomeStruct { a: 4, b: 5, c: 6 },
+    ];
+    let bytes = items.chain(vec![]).unwrap();
+
+    let view = <SeqView<SomeStruct>>::absorb_ext(&bytes).unwrap();
+    assert_eq!(view.as_bytes(), &bytes[4..]);
+    assert_eq!(view.iter().collect::<Result<Vec<_>, _>>().unwrap(), items);
+    assert_eq!(view.chain(vec![]).unwrap(), bytes);
+    assert_eq!(view.encoded_len(), bytes.len());
+
+    let view = <RestView<SomeStruct>>::absorb_ext(&bytes[4..]).unwrap();
+    assert_eq!(view.into_iter().count(), 2);
+    assert_eq!(view.chain(vec![]).unwrap(), &bytes[4..]);
+
+    // the length is checked up front, the elements on demand
+    assert!(<SeqView<SomeStruct>>::absorb_ext(&bytes[..10]).is_err());
+    let view = <RestView<SomeStruct>>::absorb_ext(&bytes[4..12]).unwrap();
+    let mut iter = view.iter();
+    assert!(iter.next().unwrap().is_ok());
+    let err = iter.next().unwrap().unwrap_err();
+    assert_eq!(err.path_string(), "[1]");
+    assert_eq!(err.offset(&bytes), Some(12));
+    assert!(iter.next().is_none());
+
+    let view = <SeqView<()>>::absorb_ext(b"\x00\x00\x00\x01\x00").unwrap();
+    let err = view.iter().next().unwrap().unwrap_err();
+    assert!(matches!(err.kind, ParseErrorKind::ZeroSizedLoop));
+
+    let slice = <&[u8]>::absorb_ext(b"\x00\x00\x00\x02ab").unwrap();
+    assert_eq!(slice, b"ab");
+    assert_eq!(slice.chain(vec![]).unwrap(), b"\x00\x00\x00\x02ab");
+    let boxed = Box::<[u8]>::from(slice);
+    assert_eq!(boxed.chain(vec![]).unwrap(), b"ab");
+    assert_eq!((&*boxed).chain(vec![]).unwrap(), b"\x00\x00\x00\x02ab");
+    assert_eq!(
+        <Vec<u8>>::absorb_ext(&slice.chain(vec![]).unwrap()).unwrap(),
+        b"ab"
+    );
+}
+
+#[derive(Debug, PartialEq, Eq, Absorb, Emit, IsCanonical)]
+struct Borrowed<'a> {
+    name: Cow<'a, str>,
+    data: Cow<'a, [u8]>,
+    raw: &'a [u8],
+    text: &'a str,
+}
+
+#[derive(Debug, PartialEq, Eq, Absorb, Emit, IsCanonical)]
+enum BorrowedInput<'pa> {
+    Text(Cow<'pa, str>),
+    Nested(Borrowed<'pa>),
+}
+
+#[test]
+fn cow() {
+    let owned = Borrowed {
+        name: Cow::Owned("name".to_string()),
+        data: Cow::Owned(vec![1, 2, 3]),
+        raw: b"raw",
+        text: "text",
+    };
+    let bytes = owned.chain(vec![]).unwrap();
+    assert_eq!(
+        bytes,
+        b"\x00\x00\x00\x04name\x00\x00\x00\x03\x01\x02\x03\x00\x00\x00\x03raw\x00\x00\x00\x04text",
+    );
+    assert_eq!(owned.encoded_len(), bytes.len());
+
+    let borrowed = Borrowed::absorb_ext(&bytes).unwrap();
+    assert!(matches!(borrowed.name, Cow::Borrowed("name")));
+    assert!(matches!(borrowed.data, Cow::Borrowed(&[1, 2, 3])));
+    assert_eq!(borrowed, owned);
+    assert!(borrowed.is_canonical());
+    assert!(BorrowedInput::Nested(borrowed).is_canonical());
+
+    // the same encoding as `String` and `Vec<u8>`
+    assert_eq!(
+        <Cow<str>>::absorb_ext(&"abc".to_string().chain(vec![]).unwrap()).unwrap(),
+        "abc"
+    );
+    let v = vec![7u8; 5].chain(vec![]).unwrap();
+    assert_eq!(
+        <Cow<[u8]>>::absorb_ext(&v).unwrap().chain(vec![]).unwrap(),
+        v
+    );
+
+    let nested = BorrowedInput::Nested(owned);
+    let bytes = nested.chain(vec![]).unwrap();
+    assert_eq!(BorrowedInput::absorb_ext(&bytes).unwrap(), nested);
+    let text = BorrowedInput::Text("text".into());
+    assert_eq!(
+        BorrowedInput::absorb_ext(&text.chain(vec![]).unwrap()).unwrap(),
+        text
+    );
+}
+
+#[test]
+fn pointers() {
+    use core::cell::{Cell, RefCell};
+    use alloc::{rc::Rc, sync::Arc};
+    use std::sync::Mutex;
+
+    let bytes = b"\x12\x23\x34\x45\x56\x67\x78";
+    let value = SomeStruct::absorb_ext(bytes).unwrap();
+    assert_eq!(*<Rc<SomeStruct>>::absorb_ext(bytes).unwrap(), value);
+    assert_eq!(*<Arc<SomeStruct>>::absorb_ext(bytes).unwrap(), value);
+    assert_eq!(
+        <Cow<u16>>::absorb_ext(b"\x00\x04").unwrap(),
+        Cow::Owned::<u16>(4)
+    );
+    assert_eq!(Rc::new(0x1234u16).chain(vec![]).unwrap(), b"\x12\x34");
+    assert_eq!(<Arc<u16>>::SIZE, Some(2));
+
+    let s = "\x00\x00\x00\x03abc".as_bytes();
+    assert_eq!(&*<Box<str>>::absorb_ext(s).unwrap(), "abc");
+    assert_eq!(&*<Rc<str>>::absorb_ext(s).unwrap(), "abc");
+    let arc = <Arc<str>>::absorb_ext(s).unwrap();
+    assert_eq!(&*arc, "abc");
+    assert_eq!(arc.chain(vec![]).unwrap(), s);
+    assert_eq!(Box::<str>::from("abc").encoded_len(), s.len());
+
+    // like `Box<[T]>`, without the length
+    let slice = <Arc<[u16]>>::absorb_ext(b"\x00\x01\x00\x02").unwrap();
+    assert_eq!(&*slice, &[1, 2]);
+    assert_eq!(slice.chain(vec![]).unwrap(), b"\x00\x01\x00\x02");
+    let slice = <Rc<[u16]>>::absorb_ext(b"\x00\x03").unwrap();
+    assert_eq!(slice.chain(vec![]).unwrap(), b"\x00\x03");
+
+    let cell = <Cell<u16>>::absorb_ext(b"\x00\x05").unwrap();
+    cell.set(6);
+    assert_eq!(cell.chain(vec![]).unwrap(), b"\x00\x06");
+
+    let cell = <RefCell<Vec<u8>>>::absorb_ext(b"\x00\x00\x00\x01\x07").unwrap();
+    cell.borrow_mut().push(8);
+    assert_eq!(cell.chain(vec![]).unwrap(), b"\x00\x00\x00\x02\x07\x08");
+    let guard = cell.borrow_mut();
+    assert!(matches!(cell.chain(vec![]), Err(EmitError::Custom(_))));
+    // the length does not panic, the emit fails
+    let cells = vec![&cell];
+    assert!(matches!(cells.chain(vec![]), Err(EmitError::Custom(_))));
+    drop(guard);
+
+    // the length prefix disagrees with the value, like a concurrent writer
+    assert!(matches!(
+        DynSized(Growing(Cell::new(1))).chain(vec![]),
+        Err(EmitError::LengthChanged {
+            prefix: 1,
+            actual: 2
+        })
+    ));
+    assert!(matches!(
+        vec![Growing(Cell::new(1))].chain(vec![]),
+        Err(EmitError::LengthChanged {
+            prefix: 1,
+            actual: 2
+        })
+    ));
+    let mutex = Mutex::new(Growing(Cell::new(1)));
+    assert!(matches!(
+        DynSized(&mutex).chain(vec![]),
+        Err(EmitError::LengthChanged { .. })
+    ));
+
+    let mutex = Arc::new(<Mutex<u16>>::absorb_ext(b"\x00\x09").unwrap());
+    assert_eq!(mutex.chain(vec![]).unwrap(), b"\x00\x09");
+    let poison = mutex.clone();
+    let _ = std::thread::spawn(move || {
+        let _guard = poison.lock().unwrap();
+        panic!("poison the mutex");
+    })
+    .join();
+    assert_eq!(mutex.encoded_len(), 2);
+    assert!(matches!(mutex.chain(vec![]), Err(EmitError::Custom(_))));
+}
+
+#[derive(Absorb, Emit)]
+struct Shared {
+    items: std::sync::Mutex<Vec<u8>>,
+    id: u8,
+}
+
+// pushes to the vector once the emit releases its lock,
+// after the length prefix of `DynSized` is emitted
+struct Meddling<'a> {
+    bytes: Vec<u8>,
+    items: &'a std::sync::Mutex<Vec<u8>>,
+    done: bool,
+}
+
+impl<'a, 'b> Extend<&'b u8> for Meddling<'a> {
+    fn extend<I: IntoIterator<Item = &'b u8>>(&mut self, iter: I) {
+        self.bytes.extend(iter);
+        if self.done || self.bytes.len() <= 4 {
+            return;
+        }
+        if let Ok(mut items) = self.items.try_lock() {
+            items.push(9);
+            self.done = true;
+        }
+    }
+}
+
+#[test]
+fn derived_emit_measured() {
+    let bytes = b"\x00\x00\x00\x07\x00\x00\x00\x02\x03\x04\x01";
+    let shared = <DynSized<Shared>>::absorb_ext(bytes).unwrap();
+    assert_eq!(shared.chain(vec![]).unwrap(), bytes);
+
+    // the vector grows after it is emitted, the derived struct measures
+    // the bytes it emitted under the one lock, not the grown vector
+    let mut buffer = Meddling {
+        bytes: vec![],
+        items: &shared.0.items,
+        done: false,
+    };
+    shared.emit::<()>(&mut buffer).unwrap();
+    assert!(buffer.done);
+    assert_eq!(buffer.bytes, bytes);
+    assert_eq!(shared.0.items.lock().unwrap().len(), 3);
+}
+
+// grows by one byte on each emit
+struct Growing(core::cell::Cell<usize>);
+
+impl EncodedLen for Growing {
+    fn encoded_len_with<L>(&self) -> usize
+    where
+        L: Limit,
+    {
+        self.0.get()
+    }
+}
+
+impl<W> Emit<W> for Growing
+where
+    W: for<'a> Extend<&'a u8>,
+{
+    fn emit<L>(&self, buffer: &mut W) -> Result<(), EmitError>
+    where
+        L: Limit,
+    {
+        self.0.set(self.0.get() + 1);
+        buffer.extend(&vec![0; self.0.get()]);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Absorb, Emit)]
+#[radiation(ordering = "acquire")]
+struct Counters {
+    flag: core::sync::atomic::AtomicBool,
+    small: core::sync::atomic::AtomicI8,
+    #[radiation(ordering = "seq_cst")]
+    medium: core::sync::atomic::AtomicU16,
+    wide: core::sync::atomic::AtomicIsize,
+}
+
+#[test]
+fn atomics() {
+    use core::sync::atomic::{AtomicU32, AtomicI32, Ordering};
+
+    let bytes = b"\xff\xfe\x12\x34\xff\xff\xff\xff\xff\xff\xff\xfd";
+    let counters = Counters::absorb_ext(bytes).unwrap();
+    assert!(counters.flag.load(Ordering::Relaxed));
+    assert_eq!(counters.small.load(Ordering::Relaxed), -2);
+    assert_eq!(counters.medium.load(Ordering::Relaxed), 0x1234);
+    assert_eq!(counters.wide.load(Ordering::Relaxed), -3);
+    assert_eq!(counters.chain(vec![]).unwrap(), bytes);
+    assert_eq!(Counters::SIZE, Some(bytes.len()));
+
+    let counter = AtomicU32::new(7);
+    counter.fetch_add(1, Ordering::Release);
+    let mut buffer = vec![];
+    counter.emit::<Acquire>(&mut buffer).unwrap();
+    assert_eq!(buffer, b"\x00\x00\x00\x08");
+    assert_eq!(<AtomicI32>::absorb_ext(&buffer).unwrap().into_inner(), 8);
+
+    // the store orderings do not make the load panic
+    struct Stored<const RELEASE: bool>;
+
+    impl<const RELEASE: bool> Limit for Stored<RELEASE> {
+        type Inner = ();
+        type Next = ();
+        const LOWER: usize = 0;
+        const UPPER: usize = usize::MAX;
+        const DESCRIPTION: &'static str = "stored";
+        const ORDERING: Ordering = if RELEASE {
+            Ordering::Release
+        } else {
+            Ordering::AcqRel
+        };
+    }
+
+    assert_eq!(counter.encoded_len_with::<Stored<true>>(), 4);
+    let mut buffer = vec![];
+    counter.emit::<Stored<true>>(&mut buffer).unwrap();
+    counter.emit::<Stored<false>>(&mut buffer).unwrap();
+    assert_eq!(buffer, b"\x00\x00\x00\x08\x00\x00\x00\x08");
+}
+
+#[test]
+fn net() {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+
+    let v4 = Ipv4Addr::new(10, 0, 0, 1);
+    assert_eq!(v4.chain(vec![]).unwrap(), b"\x0a\x00\x00\x01");
+    assert_eq!(Ipv4Addr::absorb_ext(b"\x0a\x00\x00\x01").unwrap(), v4);
+    let v6 = "fe80::1".parse::<Ipv6Addr>().unwrap();
+    assert_eq!(
+        Ipv6Addr::absorb_ext(&v6.chain(vec![]).unwrap()).unwrap(),
+        v6
+    );
+
+    let ip = IpAddr::V4(v4);
+    assert_eq!(ip.chain(vec![]).unwrap(), b"\x04\x0a\x00\x00\x01");
+    let ip = IpAddr::V6(v6);
+    let bytes = ip.chain(vec![]).unwrap();
+    assert_eq!(bytes.len(), ip.encoded_len());
+    assert_eq!(IpAddr::absorb_ext(&bytes).unwrap(), ip);
+
+    match IpAddr::absorb_ext(b"\x05") {
+        Err(nom::Err::Error(ParseError {
+            kind: ParseErrorKind::UnknownTag { tag, hint },
+            ..
+        })) => {
+            assert_eq!(tag.downcast_ref::<u8>(), Some(&5));
+            assert_eq!(hint, "IpAddr");
+        }
+        r => panic!("unexpected {r:?}"),
+    }
+
+    let addr = SocketAddrV4::new(v4, 80);
+    assert_eq!(addr.chain(vec![]).unwrap(), b"\x0a\x00\x00\x01\x00\x50");
+    assert_eq!(
+        SocketAddrV4::absorb_ext(b"\x0a\x00\x00\x01\x00\x50").unwrap(),
+        addr
+    );
+
+    // the plain encoding drops flowinfo and scope_id
+    let addr = SocketAddrV6::new(v6, 443, 7, 2);
+    let bytes = addr.chain(vec![]).unwrap();
+    assert_eq!(bytes.len(), <SocketAddrV6>::SIZE.unwrap());
+    assert_eq!(
+        SocketAddrV6::absorb_ext(&bytes).unwrap(),
+        SocketAddrV6::new(v6, 443, 0, 0)
+    );
+    let bytes = WithScope(addr).chain(vec![]).unwrap();
+    assert_eq!(&bytes[18..], b"\x00\x00\x00\x07\x00\x00\x00\x02");
+    assert_eq!(
+        <WithScope<SocketAddrV6>>::absorb_ext(&bytes).unwrap().0,
+        addr
+    );
+
+    let addr = SocketAddr::V6(addr);
+    let bytes = WithScope(addr).chain(vec![]).unwrap();
+    assert_eq!(bytes.len(), WithScope(addr).encoded_len());
+    assert_eq!(<WithScope<SocketAddr>>::absorb_ext(&bytes).unwrap().0, addr);
+    let bytes = addr.chain(vec![]).unwrap();
+    assert_eq!(bytes.len(), 1 + 16 + 2);
+    let addr = SocketAddr::V4(SocketAddrV4::new(v4, 80));
+    assert_eq!(
+        <WithScope<SocketAddr>>::absorb_ext(&WithScope(addr).chain(vec![]).unwrap())
+            .unwrap()
+            .0,
+        addr
+    );
+}
+
+#[test]
+fn time() {
+    use std::time::{Duration, SystemTime};
+
+    let duration = Duration::new(3, 5);
+    let bytes = duration.chain(vec![]).unwrap();
+    assert_eq!(bytes, b"\x00\x00\x00\x00\x00\x00\x00\x03\x00\x00\x00\x05");
+    assert_eq!(Duration::absorb_ext(&bytes).unwrap(), duration);
+    match Duration::absorb_ext(b"\x00\x00\x00\x00\x00\x00\x00\x03\x3b\x9a\xca\x00") {
+        Err(nom::Err::Error(err)) => {
+            assert!(matches!(
+                err.kind,
+                ParseErrorKind::InvalidNanos(1_000_000_000)
+            ));
+        }
+        r => panic!("unexpected {r:?}"),
+    }
+
+    let after = SystemTime::UNIX_EPOCH + Duration::new(1_700_000_000, 250_000_000);
+    let bytes = after.chain(vec![]).unwrap();
+    assert_eq!(SystemTime::absorb_ext(&bytes).unwrap(), after);
+    // before the epoch the seconds are negative, the nanoseconds are not
+    let before = SystemTime::UNIX_EPOCH - Duration::new(1, 250_000_000);
+    let bytes = before.chain(vec![]).unwrap();
+    assert_eq!(&bytes[..8], (-2i64).to_be_bytes());
+    assert_eq!(&bytes[8..], 750_000_000u32.to_be_bytes());
+    assert_eq!(SystemTime::absorb_ext(&bytes).unwrap(), before);
+
+    let timestamp = <UnixTimestamp<Millis>>::from(after);
+    let bytes = timestamp.chain(vec![]).unwrap();
+    assert_eq!(bytes, 1_700_000_000_250i64.to_be_bytes());
+    assert_eq!(
+        <UnixTimestamp<Millis>>::absorb_ext(&bytes).unwrap(),
+        timestamp
+    );
+    // truncated towards the past
+    let timestamp = UnixTimestamp::<Seconds>::from(before);
+    assert_eq!(timestamp.chain(vec![]).unwrap(), (-2i64).to_be_bytes());
+    let timestamp = UnixTimestamp::<Millis>::from(before);
+    let bytes = timestamp.chain(vec![]).unwrap();
+    assert_eq!(bytes, (-1250i64).to_be_bytes());
+    assert_eq!(
+        <UnixTimestamp<Millis>>::absorb_ext(&bytes).unwrap().0,
+        before
+    );
+
+    let far = SystemTime::UNIX_EPOCH + Duration::from_secs(300 * 365 * 24 * 3600);
+    assert!(matches!(
+        UnixTimestamp::<Nanos>::from(far).chain(vec![]),
+        Err(EmitError::TimeOverflow)
+    ));
+}
+
+#[cfg(unix)]
+#[test]
+fn paths() {
+    use std::{
+        ffi::OsString,
+        os::unix::ffi::OsStringExt,
+        path::{Path, PathBuf},
+    };
+
+    let path = PathBuf::from("/tmp/file");
+    let bytes = path.chain(vec![]).unwrap();
+    assert_eq!(bytes, b"\x00\x00\x00\x09/tmp/file");
+    assert_eq!(path.encoded_len(), bytes.len());
+    assert_eq!(PathBuf::absorb_ext(&bytes).unwrap(), path);
+    assert_eq!(&*<Box<Path>>::absorb_ext(&bytes).unwrap(), path.as_path());
+    assert_eq!(Path::new("/tmp/file").chain(vec![]).unwrap(), bytes,);
+
+    // the raw bytes on Unix, UTF-8 is required in strict mode
+    let raw = OsString::from_vec(b"a\xffb".to_vec());
+    let bytes = raw.chain(vec![]).unwrap();
+    assert_eq!(bytes, b"\x00\x00\x00\x03a\xffb");
+    assert_eq!(OsString::absorb_ext(&bytes).unwrap(), raw);
+
+    let mut buffer = vec![];
+    assert!(matches!(
+        raw.emit::<crate::Strict>(&mut buffer),
+        Err(EmitError::NonUtf8Path)
+    ));
+    match OsString::absorb::<crate::Strict>(&bytes) {
+        Err(nom::Err::Error(err)) => {
+            assert!(matches!(
+                err.kind,
+                ParseErrorKind::NonUtf8Path { valid_up_to: 1 }
+            ));
+            assert_eq!(err.offset(&bytes), Some(4));
+        }
+        r => panic!("unexpected {r:?}"),
+    }
+    let mut buffer = vec![];
+    path.emit::<crate::Strict>(&mut buffer).unwrap();
+    assert_eq!(PathBuf::absorb::<crate::Strict>(&buffer).unwrap().1, path);
+}
+
+#[derive(Debug, Absorb, Emit, IsCanonical)]
+struct Cached<T> {
+    pub payload: Vec<T>,
+    #[radiation(skip)]
+    pub hash: Option<u64>,
+    #[radiation(skip, default = std::time::Instant::now())]
+    pub seen: std::time::Instant,
+    #[radiation(prefix = u8)]
+    pub note: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Absorb, Emit)]
+#[tag(u8)]
+enum WithSkipped {
+    #[tag(1)]
+    A(#[radiation(skip, default = 7)] u32, u16),
+}
+
+#[test]
+fn skip() {
+    let bytes = b"\x00\x00\x00\x02\x12\x34\x02hi";
+    let cached = Cached::<u8>::absorb_ext(bytes).unwrap();
+    assert_eq!(cached.payload, [0x12, 0x34]);
+    assert_eq!(cached.hash, None);
+    assert!(cached.seen <= std::time::Instant::now());
+    assert_eq!(cached.note, "hi");
+
+    let cached = Cached {
+        hash: Some(1),
+        ..cached
+    };
+    assert_eq!(cached.chain(vec![]).unwrap(), bytes);
+    assert_eq!(cached.encoded_len(), bytes.len());
+    assert!(cached.is_canonical());
+
+    let value = WithSkipped::absorb_ext(b"\x01\x00\x05").unwrap();
+    assert_eq!(value, WithSkipped::A(7, 5));
+    assert_eq!(WithSkipped::A(1, 5).chain(vec![]).unwrap(), b"\x01\x00\x05");
+}
+
+#[derive(Debug, PartialEq, Eq, Absorb, Emit)]
+#[tag(u8)]
+enum Forward {
+    #[tag(1)]
+    Ping(u32),
+    #[radiation(other)]
+    Unknown {
+        tag: u8,
+        payload: Box<[u8]>,
+    },
+    Pong(u32),
+}
+
+#[derive(Debug, PartialEq, Eq, Absorb, Emit)]
+enum Delimited {
+    Known(u8),
+    #[radiation(other)]
+    Unknown(u16, Vec<u8>),
+}
+
+#[derive(Debug, PartialEq, Eq, Absorb, Emit)]
+struct Envelope {
+    pub message: Delimited,
+    pub trailer: u8,
+}
+
+#[test]
+fn other() {
+    assert_eq!(
+        Forward::absorb_ext(b"\x02\x00\x00\x00\x05").unwrap(),
+        Forward::Pong(5)
+    );
+    let bytes = b"\x07\x12\x34\x56";
+    let value = Forward::absorb_ext(bytes).unwrap();
+    assert_eq!(
+        value,
+        Forward::Unknown {
+            tag: 7,
+            payload: Box::new(*b"\x12\x34\x56"),
+        }
+    );
+    assert_eq!(value.chain(vec![]).unwrap(), bytes);
+    assert_eq!(value.encoded_len(), bytes.len());
+    assert_eq!(Forward::SIZE, None);
+
+    let bytes = b"\x00\x09\x00\x00\x00\x02\xab\xcd\xff";
+    let envelope = Envelope::absorb_ext(bytes).unwrap();
+    assert_eq!(envelope.message, Delimited::Unknown(9, vec![0xab, 0xcd]));
+    assert_eq!(envelope.trailer, 0xff);
+    assert_eq!(envelope.chain(vec![]).unwrap(), bytes);
+    let envelope = Envelope::absorb_ext(b"\x00\x00\x11\xff").unwrap();
+    assert_eq!(envelope.message, Delimited::Known(0x11));
+}
+
+#[derive(Debug, PartialEq, Eq, Absorb, Emit)]
+struct Versioned {
+    #[radiation(version)]
+    pub version: u8,
+    pub id: u16,
+    #[radiation(since = 2)]
+    pub flags: Option<u8>,
+    #[radiation(since = 3, trailing_optional)]
+    pub note: Option<String>,
+    #[radiation(trailing_optional)]
+    pub extra: Option<u32>,
+}
+
+#[derive(Debug, PartialEq, Eq, Absorb, Emit)]
+struct Contextual {
+    pub id: u16,
+    #[radiation(since = 2)]
+    pub flags: Option<u8>,
+}
+
+#[test]
+fn versioned() {
+    let layouts: &[&[u8]] = &[
+        b"\x01\x00\x07",
+        b"\x01\x00\x07\x00\x00\x00\x09",
+        b"\x02\x00\x07\x05",
+        b"\x03\x00\x07\x05\x00\x00\x00\x02hi",
+        b"\x03\x00\x07\x05\x00\x00\x00\x02hi\x00\x00\x00\x09",
+    ];
+    for bytes in layouts {
+        let value = Versioned::absorb_ext(bytes).unwrap();
+        assert_eq!(value.chain(vec![]).unwrap(), *bytes);
+        assert_eq!(value.encoded_len(), bytes.len());
+    }
+    let value = Versioned::absorb_ext(layouts[1]).unwrap();
+    assert_eq!(
+        (value.flags, value.note, value.extra),
+        (None, None, Some(9))
+    );
+    let value = Versioned::absorb_ext(layouts[3]).unwrap();
+    assert_eq!(value.flags, Some(5));
+    assert_eq!(value.note.as_deref(), Some("hi"));
+    assert_eq!(value.extra, None);
+    assert_eq!(Versioned::SIZE, None);
+
+    assert!(Versioned::absorb_ext(b"\x02\x00\x07").is_err());
+    let value = Versioned {
+        version: 2,
+        id: 7,
+        flags: None,
+        note: None,
+        extra: None,
+    };
+    match value.chain(vec![]) {
+        Err(EmitError::MissingField("flags")) => (),
+        r => panic!("unexpected {r:?}"),
+    }
+    let value = Versioned {
+        version: 3,
+        flags: Some(5),
+        extra: Some(9),
+        ..value
+    };
+    match value.chain(vec![]) {
+        Err(EmitError::MissingField("note")) => (),
+        r => panic!("unexpected {r:?}"),
+    }
+
+    let (_, value) = Contextual::absorb::<Version<1>>(b"\x00\x07").unwrap();
+    assert_eq!(value, Contextual { id: 7, flags: None });
+    let mut buffer = vec![];
+    value.emit::<Version<1>>(&mut buffer).unwrap();
+    assert_eq!(buffer, b"\x00\x07");
+    let value = Contextual::absorb_ext(b"\x00\x07\x05").unwrap();
+    assert_eq!(value.flags, Some(5));
+    assert_eq!(value.encoded_len_with::<Version<1>>(), 2);
+    assert_eq!(value.encoded_len(), 3);
+    match value.emit::<Version<1>>(&mut vec![]) {
+        Err(EmitError::UnexpectedField("flags")) => (),
+        r => panic!("unexpected {r:?}"),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Absorb, Emit)]
+#[tag(u8)]
+enum Extensible {
+    #[tag(1)]
+    V {
+        id: u8,
+        #[radiation(trailing_optional)]
+        extra: Option<u8>,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq, Absorb, Emit)]
+struct Trailed {
+    pub v: DynSized<Extensible>,
+    pub trailer: u8,
+}
+
+#[test]
+fn trailing_optional() {
+    let v = |id, extra| Extensible::V { id, extra };
+
+    // the layout before the optional field was appended
+    assert_eq!(Extensible::absorb_ext(b"\x01\x05").unwrap(), v(5, None));
+    let bytes = v(5, Some(6)).chain(vec![]).unwrap();
+    assert_eq!(bytes, b"\x01\x05\x06");
+    assert_eq!(Extensible::absorb_ext(&bytes).unwrap(), v(5, Some(6)));
+
+    // nested, the field which follows is not taken as the optional one
+    let value = Trailed {
+        v: DynSized(v(1, None)),
+        trailer: 9,
+    };
+    let bytes = value.chain(vec![]).unwrap();
+    assert_eq!(bytes, b"\x00\x00\x00\x02\x01\x01\x09");
+    assert_eq!(value.encoded_len(), bytes.len());
+    assert_eq!(Trailed::absorb_ext(&bytes).unwrap(), value);
+
+    // the elements of a sequence
+    let values = vec![
+        DynSized(v(1, None)),
+        DynSized(v(2, Some(3))),
+        DynSized(v(4, None)),
+    ];
+    let bytes = values.chain(vec![]).unwrap();
+    assert_eq!(
+        <Vec<DynSized<Extensible>>>::absorb_ext(&bytes).unwrap(),
+        values
+    );
+}